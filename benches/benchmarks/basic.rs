@@ -4,57 +4,87 @@
 // extern crate pretty_snowflake_derive;
 // use pretty_snowflake_derive::*;
 
-use criterion::{black_box, criterion_group, Criterion};
+use std::collections::HashMap;
+
+use criterion::{criterion_group, Criterion};
 use pretty_snowflake::{
-    AlphabetCodec, Generator, Label, LabeledBasicIdGenerator, LabeledLazyIdGenerator, LabeledRealtimeIdGenerator,
-    LazyGenerator, MakeLabeling, PrettyIdGenerator, RealTimeGenerator, SnowflakeIdGenerator,
+    generator, Generator, Id, Label, LabeledBasicIdGenerator, LabeledLazyIdGenerator, LabeledRealtimeIdGenerator,
+    LazyGenerator, RealTimeGenerator, SnowflakeIdGenerator,
 };
 
 #[derive(Label)]
 struct Foo;
 
+#[derive(Label)]
+struct ARatherLongEntityNameThatIsNotInlined;
+
 fn bench_generate_real_time_snowflake(c: &mut Criterion) {
     c.bench_function("real_time_snowflake", move |b| {
-        let mut generator = SnowflakeIdGenerator::<RealTimeGenerator>::default();
+        let generator = SnowflakeIdGenerator::<RealTimeGenerator>::default();
         b.iter(|| generator.next_id())
     });
 }
 
 fn bench_generate_real_time_pretty(c: &mut Criterion) {
     c.bench_function("real_time_pretty", move |b| {
-        let mut generator = LabeledRealtimeIdGenerator::<Foo>::default();
+        let generator = LabeledRealtimeIdGenerator::<Foo>::default();
         b.iter(|| generator.next_id())
     });
 }
 
 fn bench_generate_generator_snowflake(c: &mut Criterion) {
     c.bench_function("basic_snowflake", move |b| {
-        let mut generator = SnowflakeIdGenerator::<Generator>::default();
+        let generator = SnowflakeIdGenerator::<Generator>::default();
         b.iter(|| generator.next_id())
     });
 }
 
 fn bench_generate_generator_pretty(c: &mut Criterion) {
     c.bench_function("basic_pretty", move |b| {
-        let mut generator = LabeledBasicIdGenerator::<Foo>::default();
+        let generator = LabeledBasicIdGenerator::<Foo>::default();
         b.iter(|| generator.next_id())
     });
 }
 
 fn bench_generate_lazy_snowflake(c: &mut Criterion) {
     c.bench_function("lazy_snowflake", move |b| {
-        let mut generator = SnowflakeIdGenerator::<LazyGenerator>::default();
+        let generator = SnowflakeIdGenerator::<LazyGenerator>::default();
         b.iter(|| generator.next_id())
     });
 }
 
 fn bench_generate_lazy_pretty(c: &mut Criterion) {
     c.bench_function("lazy_pretty", move |b| {
-        let mut generator = LabeledLazyIdGenerator::<Foo>::default();
+        let generator = LabeledLazyIdGenerator::<Foo>::default();
+        b.iter(|| generator.next_id())
+    });
+}
+
+fn bench_generate_long_label_pretty(c: &mut Criterion) {
+    c.bench_function("long_label_pretty", move |b| {
+        let generator = LabeledRealtimeIdGenerator::<ARatherLongEntityNameThatIsNotInlined>::default();
         b.iter(|| generator.next_id())
     });
 }
 
+fn bench_relabel(c: &mut Criterion) {
+    c.bench_function("relabel", move |b| {
+        let id: Id<Foo> = generator::next_id();
+        b.iter(|| id.unchecked_relabel::<ARatherLongEntityNameThatIsNotInlined>())
+    });
+}
+
+fn bench_relabel_generic(c: &mut Criterion) {
+    c.bench_function("relabel_generic", move |b| {
+        let id: Id<Foo> = generator::next_id();
+        b.iter(|| id.unchecked_relabel::<HashMap<String, ARatherLongEntityNameThatIsNotInlined>>())
+    });
+}
+
+fn bench_generator_next_id(c: &mut Criterion) {
+    c.bench_function("generator_next_id", move |b| b.iter(generator::next_id::<Foo>));
+}
+
 criterion_group! {
     name = basic;
     config = Criterion::default().with_profiler(super::super::profiler::FlamegraphProfiler::new(100));
@@ -64,5 +94,9 @@ criterion_group! {
         bench_generate_lazy_snowflake,
         bench_generate_real_time_pretty,
         bench_generate_generator_pretty,
-        bench_generate_lazy_pretty
+        bench_generate_lazy_pretty,
+        bench_generate_long_label_pretty,
+        bench_relabel,
+        bench_relabel_generic,
+        bench_generator_next_id
 }
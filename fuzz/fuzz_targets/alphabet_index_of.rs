@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pretty_snowflake::{Alphabet, AlphabetCodec, Codec};
+
+fuzz_target!(|input: (String, char, String)| {
+    let (elements, c, value) = input;
+    let alphabet = Alphabet::new(elements);
+    if let Some(pos) = alphabet.index_of(c) {
+        assert_eq!(alphabet.value_of(pos), c);
+    }
+
+    let _ = AlphabetCodec::new(alphabet).decode(&value);
+});
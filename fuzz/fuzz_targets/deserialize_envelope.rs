@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pretty_snowflake::envelope::Envelope;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(envelope) = serde_json::from_slice::<Envelope<String>>(data) {
+        let _ = format!("{envelope} {envelope:?}");
+        let _ = serde_json::to_vec(&envelope);
+    }
+
+    let _ = serde_json::from_slice::<Envelope<Option<i64>>>(data);
+});
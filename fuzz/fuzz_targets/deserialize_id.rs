@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pretty_snowflake::Id;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(id) = serde_json::from_slice::<Id<i64>>(data) {
+        let _ = format!("{id} {id:?} {id:#}");
+        let _ = serde_json::to_vec(&id);
+    }
+
+    let _ = serde_json::from_slice::<Id<()>>(data);
+});
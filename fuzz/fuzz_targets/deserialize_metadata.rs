@@ -0,0 +1,13 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pretty_snowflake::envelope::MetaData;
+use std::collections::HashMap;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(metadata) = serde_json::from_slice::<MetaData<i64>>(data) {
+        let _ = format!("{metadata} {metadata:?}");
+        let _ = serde_json::to_vec(&metadata);
+        let _ = HashMap::<String, String>::from(metadata);
+    }
+});
@@ -0,0 +1,22 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pretty_snowflake::{Alphabet, AlphabetCodec, IdPrettifier};
+
+fuzz_target!(|rep: &str| {
+    let default = IdPrettifier::<AlphabetCodec>::default();
+    if let Ok(seed) = default.to_id_seed(rep) {
+        assert!(default.is_valid(rep));
+        let _ = default.prettify(seed);
+    }
+
+    let custom = IdPrettifier {
+        encoder: AlphabetCodec::new(Alphabet::new("ABC")),
+        parts_size: 2,
+        delimiter: '_'.to_string(),
+        leading_zeros: false,
+        ..IdPrettifier::default()
+    };
+    let _ = custom.to_id_seed(rep);
+    let _ = custom.is_valid(rep);
+});
@@ -1,15 +1,426 @@
+use heck::{ToKebabCase, ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToUpperCamelCase};
 use proc_macro::{self, TokenStream};
+use proc_macro2::TokenStream as TokenStream2;
 use quote::quote;
-use syn::DeriveInput;
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::{
+    parse_quote, Data, DeriveInput, Expr, Fields, GenericArgument, GenericParam, Lit, Meta, NestedMeta, PathArguments,
+    Type,
+};
 
-#[proc_macro_derive(Label)]
+/// Derives `Label` for a type. By default the label is the type's name, via `MakeLabeling`. The
+/// label may be customized with the `label` attribute:
+///
+/// - `#[label(name = "ord")]` uses the given name, via `CustomLabeling`.
+/// - `#[label(rename_all = "snake_case")]` converts the type's name to the given case, via
+///   `CustomLabeling`. Supported cases are those of serde: `lowercase`, `UPPERCASE`, `PascalCase`,
+///   `camelCase`, `snake_case`, `SCREAMING_SNAKE_CASE` and `kebab-case`.
+/// - `#[label(none)]` does not label the type, via `NoLabeling`.
+///
+/// Independently, `#[label(prefix = "ord")]` supplies a short code that prefixes the string form of
+/// the type's ids; e.g., `ord_HPJD-72036-HAPK-58077`. The prefix must be non-empty and ASCII
+/// alphanumeric.
+///
+/// With the `inventory` feature of `pretty_snowflake`, `#[label(register)]` adds a non-generic type
+/// to the label registry at link time, so that its ids may be parsed via `registry::parse_any()`.
+///
+/// For generic types with a custom label, the labels of the type parameters are appended; e.g.,
+/// `Page<Order>` with `#[label(name = "page")]` is labeled `page<Order>`. These type parameters are
+/// then required to implement `Label`.
+///
+/// For non-generic types, `Label::label()` is implemented with a string literal, so it neither
+/// allocates nor consults the label interner.
+#[proc_macro_derive(Label, attributes(label))]
 pub fn label_derive(input: TokenStream) -> TokenStream {
-    let DeriveInput { ident, .. } = syn::parse_macro_input!(input);
-    let output = quote! {
-        impl ::pretty_snowflake::Label for #ident {
-            type Labeler = ::pretty_snowflake::MakeLabeling<Self>;
-            fn labeler() -> Self::Labeler { ::pretty_snowflake::MakeLabeling::default() }
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    label_impl(input).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+enum LabelOption {
+    Default,
+    Name(String),
+    None,
+}
+
+fn rename(ident: &str, rule: &str) -> Option<String> {
+    match rule {
+        "lowercase" => Some(ident.to_lowercase()),
+        "UPPERCASE" => Some(ident.to_uppercase()),
+        "PascalCase" => Some(ident.to_upper_camel_case()),
+        "camelCase" => Some(ident.to_lower_camel_case()),
+        "snake_case" => Some(ident.to_snake_case()),
+        "SCREAMING_SNAKE_CASE" => Some(ident.to_shouty_snake_case()),
+        "kebab-case" => Some(ident.to_kebab_case()),
+        _ => None,
+    }
+}
+
+struct LabelOptions {
+    label: LabelOption,
+    prefix: Option<String>,
+    register: bool,
+}
+
+fn parse_label_options(input: &DeriveInput) -> syn::Result<LabelOptions> {
+    let mut option = LabelOption::Default;
+    let mut prefix = None;
+    let mut register = false;
+
+    for attr in input.attrs.iter().filter(|a| a.path.is_ident("label")) {
+        let nested = match attr.parse_meta()? {
+            Meta::List(list) => list.nested,
+            meta => return Err(syn::Error::new(meta.span(), "expected #[label(...)]")),
+        };
+
+        for item in nested {
+            if matches!(&item, NestedMeta::Meta(Meta::Path(path)) if path.is_ident("register")) {
+                register = true;
+                continue;
+            }
+
+            if let NestedMeta::Meta(Meta::NameValue(nv)) = &item {
+                if nv.path.is_ident("prefix") {
+                    let value = match &nv.lit {
+                        Lit::Str(s) if is_valid_prefix(&s.value()) => s.value(),
+                        lit => {
+                            return Err(syn::Error::new(
+                                lit.span(),
+                                "expected a non-empty, ASCII alphanumeric string literal",
+                            ))
+                        },
+                    };
+
+                    if prefix.replace(value).is_some() {
+                        return Err(syn::Error::new(nv.span(), "`prefix` may only be specified once"));
+                    }
+                    continue;
+                }
+            }
+
+            if !matches!(option, LabelOption::Default) {
+                return Err(syn::Error::new(
+                    item.span(),
+                    "only one of `name`, `rename_all` or `none` may be specified",
+                ));
+            }
+
+            option = match item {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("none") => LabelOption::None,
+                NestedMeta::Meta(Meta::NameValue(nv)) => {
+                    let value = match &nv.lit {
+                        Lit::Str(s) => s.value(),
+                        lit => return Err(syn::Error::new(lit.span(), "expected a string literal")),
+                    };
+
+                    if nv.path.is_ident("name") {
+                        LabelOption::Name(value)
+                    } else if nv.path.is_ident("rename_all") {
+                        let renamed = rename(&input.ident.to_string(), &value)
+                            .ok_or_else(|| syn::Error::new(nv.lit.span(), format!("unknown rename rule `{value}`")))?;
+                        LabelOption::Name(renamed)
+                    } else {
+                        return Err(syn::Error::new(
+                            nv.path.span(),
+                            "unknown label option; expected `name`, `rename_all`, `none`, `prefix` or `register`",
+                        ));
+                    }
+                },
+                other => {
+                    return Err(syn::Error::new(
+                        other.span(),
+                        "unknown label option; expected `name`, `rename_all`, `none`, `prefix` or `register`",
+                    ))
+                },
+            };
+        }
+    }
+
+    Ok(LabelOptions { label: option, prefix, register })
+}
+
+fn is_valid_prefix(prefix: &str) -> bool {
+    !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+fn label_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let LabelOptions { label: option, prefix, register } = parse_label_options(&input)?;
+    let ident = &input.ident;
+
+    let type_params: Vec<_> = input
+        .generics
+        .params
+        .iter()
+        .filter_map(|p| match p {
+            GenericParam::Type(t) => Some(t.ident.clone()),
+            _ => None,
+        })
+        .collect();
+
+    let static_label = match &option {
+        _ if !type_params.is_empty() => None,
+        LabelOption::Default => Some(ident.unraw().to_string()),
+        LabelOption::Name(name) => Some(name.clone()),
+        LabelOption::None => Some(String::new()),
+    };
+
+    let label_fn = static_label.map(|label| {
+        quote! {
+            fn label() -> &'static str { #label }
+        }
+    });
+
+    let prefix_fn = prefix.map(|prefix| {
+        quote! {
+            fn prefix() -> ::std::option::Option<&'static str> { ::std::option::Option::Some(#prefix) }
+        }
+    });
+
+    if register && !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "#[label(register)] is not supported for generic types",
+        ));
+    }
+
+    let registration = register.then(|| {
+        quote! {
+            ::pretty_snowflake::__private::inventory::submit! {
+                ::pretty_snowflake::registry::LabelRegistration::new::<#ident>()
+            }
+        }
+    });
+
+    let mut generics = input.generics.clone();
+    let (labeler_type, labeler) = match option {
+        LabelOption::Default => (
+            quote! { ::pretty_snowflake::MakeLabeling<Self> },
+            quote! { ::pretty_snowflake::MakeLabeling::default() },
+        ),
+
+        LabelOption::None => (quote! { ::pretty_snowflake::NoLabeling }, quote! { ::pretty_snowflake::NoLabeling }),
+
+        LabelOption::Name(name) if type_params.is_empty() => (
+            quote! { ::pretty_snowflake::CustomLabeling },
+            quote! { ::pretty_snowflake::CustomLabeling::new(#name) },
+        ),
+
+        LabelOption::Name(name) => {
+            let where_clause = generics.make_where_clause();
+            for param in &type_params {
+                where_clause.predicates.push(parse_quote!(#param: ::pretty_snowflake::Label));
+            }
+
+            (
+                quote! { ::pretty_snowflake::CustomLabeling },
+                quote! {
+                    let params = [#(::pretty_snowflake::__private::param_label::<#type_params>()),*];
+                    ::pretty_snowflake::CustomLabeling::new(::std::format!("{}<{}>", #name, params.join(",")))
+                },
+            )
+        },
+    };
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::pretty_snowflake::Label for #ident #ty_generics #where_clause {
+            type Labeler = #labeler_type;
+            fn labeler() -> Self::Labeler { #labeler }
+            #label_fn
+            #prefix_fn
+        }
+
+        #registration
+    })
+}
+
+/// Derives the boilerplate for a newtype around `Id<T>`; e.g., `struct OrderId(Id<Order>)`:
+/// `Display`, `FromStr`, `Serialize`, `Deserialize`, `From<i64>`, conversions to and from `Id<T>`
+/// and a `next()` constructor.
+///
+/// By default, ids are minted via `generator::next_id()` and prettified with
+/// `generator::prettifier()`. The newtype may be bound to a specific generator or prettifier:
+///
+/// ```ignore
+/// #[derive(PrettyId)]
+/// #[pretty_id(generator = "ORDER_ID_GENERATOR", prettifier = "ORDER_PRETTIFIER")]
+/// struct OrderId(Id<Order>);
+/// ```
+///
+/// The `generator` expression must provide `next_id()` returning `Id<T>`, and the `prettifier`
+/// expression must provide an `IdPrettifier`, such as a `static` behind a `Lazy`.
+#[proc_macro_derive(PrettyId, attributes(pretty_id))]
+pub fn pretty_id_derive(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    pretty_id_impl(input).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+struct PrettyIdOptions {
+    generator: Option<Expr>,
+    prettifier: Option<Expr>,
+}
+
+fn parse_pretty_id_options(input: &DeriveInput) -> syn::Result<PrettyIdOptions> {
+    let mut options = PrettyIdOptions { generator: None, prettifier: None };
+
+    for attr in input.attrs.iter().filter(|a| a.path.is_ident("pretty_id")) {
+        let nested = match attr.parse_meta()? {
+            Meta::List(list) => list.nested,
+            meta => return Err(syn::Error::new(meta.span(), "expected #[pretty_id(...)]")),
+        };
+
+        for item in nested {
+            let name_value = match item {
+                NestedMeta::Meta(Meta::NameValue(nv)) => nv,
+                other => return Err(syn::Error::new(other.span(), "expected `name = \"value\"`")),
+            };
+
+            let expr = match &name_value.lit {
+                Lit::Str(s) => s.parse::<Expr>()?,
+                lit => return Err(syn::Error::new(lit.span(), "expected a string literal")),
+            };
+
+            if name_value.path.is_ident("generator") {
+                options.generator = Some(expr);
+            } else if name_value.path.is_ident("prettifier") {
+                options.prettifier = Some(expr);
+            } else {
+                return Err(syn::Error::new(
+                    name_value.path.span(),
+                    "unknown pretty_id option; expected `generator` or `prettifier`",
+                ));
+            }
         }
+    }
+
+    Ok(options)
+}
+
+/// Finds the `T` of the single `Id<T>` field of a tuple struct.
+fn labeled_type(input: &DeriveInput) -> syn::Result<Type> {
+    let error = || syn::Error::new(input.ident.span(), "PrettyId can only be derived for a newtype around `Id<T>`");
+
+    let field = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0],
+            _ => return Err(error()),
+        },
+        _ => return Err(error()),
+    };
+
+    let segment = match &field.ty {
+        Type::Path(type_path) => type_path.path.segments.last().ok_or_else(error)?,
+        _ => return Err(error()),
     };
-    output.into()
+
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) if segment.ident == "Id" && args.args.len() == 1 => match &args.args[0] {
+            GenericArgument::Type(ty) => Ok(ty.clone()),
+            _ => Err(error()),
+        },
+        _ => Err(error()),
+    }
+}
+
+fn pretty_id_impl(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let options = parse_pretty_id_options(&input)?;
+    let labeled = labeled_type(&input)?;
+    let ident = &input.ident;
+
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(#labeled: ::pretty_snowflake::Label));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut de_generics = generics.clone();
+    de_generics.params.insert(0, parse_quote!('de));
+    let (de_impl_generics, ..) = de_generics.split_for_impl();
+
+    let next_id = options.generator.map_or_else(
+        || quote! { ::pretty_snowflake::generator::next_id::<#labeled>() },
+        |generator| quote! { (#generator).next_id() },
+    );
+    let prettifier = options.prettifier.map_or_else(
+        || quote! { ::pretty_snowflake::generator::prettifier() },
+        |prettifier| quote! { #prettifier },
+    );
+
+    let serde = quote! { ::pretty_snowflake::__private::serde };
+
+    Ok(quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Generates the next id.
+            pub fn next() -> Self {
+                Self(#next_id)
+            }
+
+            #[inline]
+            pub const fn id(&self) -> &::pretty_snowflake::Id<#labeled> {
+                &self.0
+            }
+
+            #[inline]
+            pub fn into_inner(self) -> ::pretty_snowflake::Id<#labeled> {
+                self.0
+            }
+        }
+
+        impl #impl_generics ::std::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl #impl_generics ::std::str::FromStr for #ident #ty_generics #where_clause {
+            type Err = ::pretty_snowflake::ConversionError;
+
+            fn from_str(rep: &str) -> ::std::result::Result<Self, Self::Err> {
+                ::pretty_snowflake::Id::parse_with(rep, &#prettifier).map(Self)
+            }
+        }
+
+        impl #impl_generics ::std::convert::From<i64> for #ident #ty_generics #where_clause {
+            fn from(snowflake: i64) -> Self {
+                Self(::pretty_snowflake::Id::from_snowflake(snowflake, &#prettifier))
+            }
+        }
+
+        impl #impl_generics ::std::convert::From<::pretty_snowflake::Id<#labeled>> for #ident #ty_generics #where_clause {
+            fn from(id: ::pretty_snowflake::Id<#labeled>) -> Self {
+                Self(id)
+            }
+        }
+
+        impl #impl_generics ::std::convert::From<#ident #ty_generics> for ::pretty_snowflake::Id<#labeled> #where_clause {
+            fn from(id: #ident #ty_generics) -> Self {
+                id.0
+            }
+        }
+
+        impl #impl_generics ::std::convert::AsRef<::pretty_snowflake::Id<#labeled>> for #ident #ty_generics #where_clause {
+            fn as_ref(&self) -> &::pretty_snowflake::Id<#labeled> {
+                &self.0
+            }
+        }
+
+        impl #impl_generics #serde::Serialize for #ident #ty_generics #where_clause {
+            fn serialize<S>(&self, serializer: S) -> ::std::result::Result<S::Ok, S::Error>
+            where
+                S: #serde::Serializer,
+            {
+                #serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl #de_impl_generics #serde::Deserialize<'de> for #ident #ty_generics #where_clause {
+            fn deserialize<D>(deserializer: D) -> ::std::result::Result<Self, D::Error>
+            where
+                D: #serde::Deserializer<'de>,
+            {
+                <::pretty_snowflake::Id<#labeled> as #serde::Deserialize<'de>>::deserialize(deserializer).map(Self)
+            }
+        }
+    })
 }
@@ -0,0 +1,63 @@
+//! `diesel` mappings of `Id<T>` and `SnowflakeId` onto `BigInt` columns. The pretty form of a
+//! loaded `Id<T>` is reconstructed using `generator::prettifier()`.
+
+use crate::snowflake::Id as SnowflakeId;
+use crate::{generator, Id, Label};
+use diesel::backend::Backend;
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::BigInt;
+
+#[allow(dead_code)]
+#[derive(AsExpression, FromSqlRow)]
+#[diesel(foreign_derive)]
+#[diesel(sql_type = BigInt)]
+struct SnowflakeIdProxy(SnowflakeId);
+
+#[allow(dead_code)]
+#[derive(AsExpression, FromSqlRow)]
+#[diesel(foreign_derive)]
+#[diesel(sql_type = BigInt)]
+struct IdProxy<T: ?Sized>(Id<T>);
+
+impl<DB> ToSql<BigInt, DB> for SnowflakeId
+where
+    DB: Backend,
+    i64: ToSql<BigInt, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        <i64 as ToSql<BigInt, DB>>::to_sql(self.as_ref(), out)
+    }
+}
+
+impl<DB> FromSql<BigInt, DB> for SnowflakeId
+where
+    DB: Backend,
+    i64: FromSql<BigInt, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        <i64 as FromSql<BigInt, DB>>::from_sql(bytes).map(Self::from)
+    }
+}
+
+impl<T: ?Sized, DB> ToSql<BigInt, DB> for Id<T>
+where
+    DB: Backend,
+    i64: ToSql<BigInt, DB>,
+{
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, DB>) -> serialize::Result {
+        <i64 as ToSql<BigInt, DB>>::to_sql(self.snowflake().as_ref(), out)
+    }
+}
+
+impl<T: Label + ?Sized, DB> FromSql<BigInt, DB> for Id<T>
+where
+    DB: Backend,
+    i64: FromSql<BigInt, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        let snowflake = <i64 as FromSql<BigInt, DB>>::from_sql(bytes)?;
+        Ok(Self::from_snowflake(snowflake, &generator::prettifier()))
+    }
+}
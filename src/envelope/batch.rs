@@ -0,0 +1,421 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+use iso8601_timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+
+use crate::envelope::{Correlation, Envelope, Extension, MergeError, MergePolicy, ReceivedAt};
+use crate::{generator, Id, Label};
+
+/// The bounds of a time window, including its start and excluding its end.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimeWindow {
+    pub start: Timestamp,
+    pub end: Timestamp,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, timestamp: Timestamp) -> bool {
+        self.start <= timestamp && timestamp < self.end
+    }
+}
+
+impl fmt::Display for TimeWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}, {})", self.start, self.end)
+    }
+}
+
+/// Records the envelopes an envelope was combined from, via [`EnvelopeBatch::combine`], in its
+/// metadata extensions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchLineage {
+    /// The correlation ids of the combined envelopes, in batch order.
+    pub correlation_ids: Vec<Id<()>>,
+    /// The message ids of the combined envelopes, in batch order.
+    pub message_ids: Vec<Id<()>>,
+    pub earliest: Timestamp,
+    pub latest: Timestamp,
+}
+
+impl Extension for BatchLineage {
+    const KEY: &'static str = "batch_lineage";
+}
+
+/// Envelopes aggregated into a batch, such as over a time window, which tracks the correlation ids
+/// of its envelopes and the range of their receipt timestamps.
+pub struct EnvelopeBatch<T: Label> {
+    envelopes: Vec<Envelope<T>>,
+}
+
+impl<T> fmt::Debug for EnvelopeBatch<T>
+where
+    T: fmt::Debug + Label + Send,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.envelopes.iter()).finish()
+    }
+}
+
+impl<T: Label + Clone> Clone for EnvelopeBatch<T> {
+    fn clone(&self) -> Self {
+        Self { envelopes: self.envelopes.clone() }
+    }
+}
+
+impl<T: Label> Default for EnvelopeBatch<T> {
+    fn default() -> Self {
+        Self { envelopes: Vec::new() }
+    }
+}
+
+impl<T> EnvelopeBatch<T>
+where
+    T: Label + Send + Sync,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, envelope: Envelope<T>) {
+        self.envelopes.push(envelope);
+    }
+
+    pub const fn len(&self) -> usize {
+        self.envelopes.len()
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.envelopes.is_empty()
+    }
+
+    pub const fn envelopes(&self) -> &[Envelope<T>] {
+        self.envelopes.as_slice()
+    }
+
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn into_envelopes(self) -> Vec<Envelope<T>> {
+        self.envelopes
+    }
+
+    /// The correlation ids of the batched envelopes, in batch order.
+    pub fn correlation_ids(&self) -> impl Iterator<Item = &Id<T>> {
+        self.envelopes.iter().map(|envelope| envelope.metadata().correlation())
+    }
+
+    /// The earliest receipt timestamp of the batched envelopes.
+    pub fn earliest(&self) -> Option<Timestamp> {
+        self.envelopes.iter().map(ReceivedAt::recv_timestamp).min()
+    }
+
+    /// The latest receipt timestamp of the batched envelopes.
+    pub fn latest(&self) -> Option<Timestamp> {
+        self.envelopes.iter().map(ReceivedAt::recv_timestamp).max()
+    }
+
+    /// Combines the batched envelopes into one, merging their metadata according to `policy` and
+    /// combining their contents via `f`, in batch order.
+    ///
+    /// The combined envelope is a new message: it has a new message id and no causal lineage of its
+    /// own. The correlation and message ids of the batched envelopes and the range of their receipt
+    /// timestamps are recorded in its [`BatchLineage`] extension. An empty batch combines into
+    /// nothing.
+    pub fn combine_with<F>(self, policy: MergePolicy, mut f: F) -> Result<Option<Envelope<T>>, MergeError>
+    where
+        F: FnMut(T, T) -> T,
+    {
+        let (Some(earliest), Some(latest)) = (self.earliest(), self.latest()) else {
+            return Ok(None);
+        };
+
+        let lineage = BatchLineage {
+            correlation_ids: self.correlation_ids().map(Id::unchecked_relabel).collect(),
+            message_ids: self
+                .envelopes
+                .iter()
+                .map(|envelope| envelope.metadata().message_id().unchecked_relabel())
+                .collect(),
+            earliest,
+            latest,
+        };
+
+        let mut envelopes = self.envelopes.into_iter();
+        let first = envelopes.next().map(Envelope::into_parts);
+        let Some((mut metadata, mut content)) = first else {
+            return Ok(None);
+        };
+        for envelope in envelopes {
+            let (other_metadata, other_content) = envelope.into_parts();
+            metadata = metadata.merge(other_metadata, policy)?;
+            content = f(content, other_content);
+        }
+
+        let mut metadata = metadata
+            .with_message_id(generator::next_id())
+            .with_lineage(None, Vec::new());
+        metadata.insert(lineage);
+        Ok(Some(Envelope::from_parts(metadata, content)))
+    }
+
+    /// Combines the batched envelopes into one by adding their contents, keeping the metadata
+    /// received latest as `Add` does; see [`EnvelopeBatch::combine_with`].
+    pub fn combine(self) -> Option<Envelope<T>>
+    where
+        T: std::ops::Add<Output = T>,
+    {
+        // Keeping the latest metadata never conflicts.
+        self.combine_with(MergePolicy::KeepLatest, std::ops::Add::add)
+            .ok()
+            .flatten()
+    }
+
+    /// Splits the batch into tumbling windows of `size`, aligned to the UNIX epoch, by receipt
+    /// timestamp. Only windows holding envelopes are returned, in time order, with their envelopes
+    /// in receipt order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` is zero.
+    pub fn tumbling(self, size: Duration) -> Vec<(TimeWindow, Self)> {
+        assert!(!size.is_zero(), "window size must be positive");
+        let size = size.as_nanos() as i128;
+
+        let mut windows: BTreeMap<i128, Self> = BTreeMap::new();
+        for envelope in self.into_sorted() {
+            let start = nanos_since_epoch(envelope.recv_timestamp()).div_euclid(size) * size;
+            windows.entry(start).or_default().push(envelope);
+        }
+
+        windows
+            .into_iter()
+            .map(|(start, batch)| (time_window(start, size), batch))
+            .collect()
+    }
+
+    /// Splits the batch into sliding windows of `size`, starting every `slide` since the UNIX
+    /// epoch, by receipt timestamp. An envelope is copied into every window containing it. Only
+    /// windows holding envelopes are returned, in time order, with their envelopes in receipt
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` or `slide` is zero.
+    pub fn sliding(self, size: Duration, slide: Duration) -> Vec<(TimeWindow, Self)>
+    where
+        T: Clone,
+    {
+        assert!(!size.is_zero(), "window size must be positive");
+        assert!(!slide.is_zero(), "window slide must be positive");
+        let size = size.as_nanos() as i128;
+        let slide = slide.as_nanos() as i128;
+
+        let mut windows: BTreeMap<i128, Self> = BTreeMap::new();
+        for envelope in self.into_sorted() {
+            let at = nanos_since_epoch(envelope.recv_timestamp());
+            let last = at.div_euclid(slide);
+            let first = (at - size).div_euclid(slide) + 1;
+            for n in first..=last {
+                windows.entry(n * slide).or_default().push(envelope.clone());
+            }
+        }
+
+        windows
+            .into_iter()
+            .map(|(start, batch)| (time_window(start, size), batch))
+            .collect()
+    }
+
+    fn into_sorted(self) -> Vec<Envelope<T>> {
+        let mut envelopes = self.envelopes;
+        envelopes.sort_by_key(ReceivedAt::recv_timestamp);
+        envelopes
+    }
+}
+
+impl<T: Label + Send> FromIterator<Envelope<T>> for EnvelopeBatch<T> {
+    fn from_iter<I: IntoIterator<Item = Envelope<T>>>(iter: I) -> Self {
+        Self { envelopes: iter.into_iter().collect() }
+    }
+}
+
+impl<T: Label + Send> Extend<Envelope<T>> for EnvelopeBatch<T> {
+    fn extend<I: IntoIterator<Item = Envelope<T>>>(&mut self, iter: I) {
+        self.envelopes.extend(iter);
+    }
+}
+
+impl<T: Label> IntoIterator for EnvelopeBatch<T> {
+    type Item = Envelope<T>;
+    type IntoIter = std::vec::IntoIter<Envelope<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.envelopes.into_iter()
+    }
+}
+
+fn nanos_since_epoch(timestamp: Timestamp) -> i128 {
+    timestamp.duration_since(Timestamp::UNIX_EPOCH).whole_nanoseconds()
+}
+
+fn timestamp_at(nanos: i128) -> Timestamp {
+    let magnitude = nanos.unsigned_abs();
+    let offset = Duration::new((magnitude / 1_000_000_000) as u64, (magnitude % 1_000_000_000) as u32);
+    if nanos < 0 {
+        Timestamp::UNIX_EPOCH - offset
+    } else {
+        Timestamp::UNIX_EPOCH + offset
+    }
+}
+
+fn time_window(start: i128, size: i128) -> TimeWindow {
+    TimeWindow {
+        start: timestamp_at(start),
+        end: timestamp_at(start + size),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::*;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::envelope::MetaData;
+    use crate::MakeLabeling;
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Count(u32);
+
+    impl Label for Count {
+        type Labeler = MakeLabeling<Self>;
+
+        fn labeler() -> Self::Labeler {
+            MakeLabeling::default()
+        }
+    }
+
+    impl std::ops::Add for Count {
+        type Output = Self;
+
+        fn add(self, rhs: Self) -> Self::Output {
+            Self(self.0 + rhs.0)
+        }
+    }
+
+    fn envelope_at(ts: &str, count: u32) -> Envelope<Count> {
+        let recv_timestamp = assert_some!(Timestamp::parse(ts));
+        let metadata = MetaData::from_parts(generator::next_id(), recv_timestamp, None);
+        Envelope::from_parts(metadata, Count(count))
+    }
+
+    fn batch() -> EnvelopeBatch<Count> {
+        vec![
+            envelope_at("2022-11-30T03:43:12.000Z", 4),
+            envelope_at("2022-11-30T03:43:01.500Z", 1),
+            envelope_at("2022-11-30T03:43:09.999Z", 2),
+            envelope_at("2022-11-30T03:43:10.000Z", 3),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    fn contents(batch: &EnvelopeBatch<Count>) -> Vec<u32> {
+        batch.envelopes().iter().map(|e| e.as_ref().0).collect()
+    }
+
+    #[test]
+    fn test_batch_tracks_correlation_ids_and_timestamps() {
+        let batch = batch();
+        assert_eq!(batch.len(), 4);
+        assert_eq!(batch.correlation_ids().count(), 4);
+        assert_eq!(batch.earliest(), Timestamp::parse("2022-11-30T03:43:01.500Z"));
+        assert_eq!(batch.latest(), Timestamp::parse("2022-11-30T03:43:12.000Z"));
+
+        let empty = EnvelopeBatch::<Count>::new();
+        assert_none!(empty.earliest());
+        assert_none!(empty.combine());
+    }
+
+    #[test]
+    fn test_combine_records_lineage() {
+        let batch = batch();
+        let correlation_ids: Vec<_> = batch.correlation_ids().map(Id::num).collect();
+        let message_ids: Vec<_> = batch.envelopes().iter().map(|e| e.metadata().message_id().num()).collect();
+        let latest = batch.envelopes()[0].metadata().clone();
+
+        let combined = assert_some!(batch.combine());
+        assert_eq!(combined.as_ref(), &Count(10));
+        assert_eq!(combined.metadata().correlation(), latest.correlation());
+        assert_eq!(combined.metadata().recv_timestamp(), latest.recv_timestamp());
+        assert!(!message_ids.contains(&combined.metadata().message_id().num()));
+        assert_none!(combined.metadata().causation_id());
+        assert!(combined.metadata().parent_ids().is_empty());
+
+        let lineage = assert_some!(combined.metadata().get::<BatchLineage>());
+        assert_eq!(
+            lineage.correlation_ids.iter().map(Id::num).collect::<Vec<_>>(),
+            correlation_ids
+        );
+        assert_eq!(lineage.message_ids.iter().map(Id::num).collect::<Vec<_>>(), message_ids);
+        assert_eq!(Some(lineage.earliest), Timestamp::parse("2022-11-30T03:43:01.500Z"));
+        assert_eq!(Some(lineage.latest), Timestamp::parse("2022-11-30T03:43:12.000Z"));
+    }
+
+    #[test]
+    fn test_combine_with_policy() {
+        let combined = assert_ok!(batch().combine_with(MergePolicy::KeepEarliest, |a, b| Count(a.0.max(b.0))));
+        let combined = assert_some!(combined);
+        assert_eq!(combined.as_ref(), &Count(4));
+        assert_eq!(
+            Some(combined.metadata().recv_timestamp()),
+            Timestamp::parse("2022-11-30T03:43:01.500Z")
+        );
+    }
+
+    #[test]
+    fn test_tumbling_windows() {
+        let windows = batch().tumbling(Duration::from_secs(10));
+        let actual: Vec<_> = windows.iter().map(|(w, b)| (w.to_string(), contents(b))).collect();
+        assert_eq!(
+            actual,
+            vec![
+                (
+                    "[2022-11-30T03:43:00.000Z, 2022-11-30T03:43:10.000Z)".to_string(),
+                    vec![1, 2]
+                ),
+                (
+                    "[2022-11-30T03:43:10.000Z, 2022-11-30T03:43:20.000Z)".to_string(),
+                    vec![3, 4]
+                ),
+            ]
+        );
+        assert!(windows
+            .iter()
+            .all(|(window, batch)| batch.envelopes().iter().all(|e| window.contains(e.recv_timestamp()))));
+    }
+
+    #[test]
+    fn test_sliding_windows() {
+        let windows = batch().sliding(Duration::from_secs(10), Duration::from_secs(5));
+        let actual: Vec<_> = windows.iter().map(|(w, b)| (w.start.to_string(), contents(b))).collect();
+        assert_eq!(
+            actual,
+            vec![
+                ("2022-11-30T03:42:55.000Z".to_string(), vec![1]),
+                ("2022-11-30T03:43:00.000Z".to_string(), vec![1, 2]),
+                ("2022-11-30T03:43:05.000Z".to_string(), vec![2, 3, 4]),
+                ("2022-11-30T03:43:10.000Z".to_string(), vec![3, 4]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_windows_before_epoch() {
+        let batch: EnvelopeBatch<Count> = vec![envelope_at("1969-12-31T23:59:59.000Z", 1)].into_iter().collect();
+        let windows = batch.tumbling(Duration::from_secs(10));
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].0.start.to_string(), "1969-12-31T23:59:50.000Z");
+        assert_eq!(windows[0].0.end, Timestamp::UNIX_EPOCH);
+    }
+}
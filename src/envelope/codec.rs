@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use thiserror::Error;
+
+use crate::envelope::metadata::{
+    CAUSATION_PRETTY_ID_KEY, CAUSATION_SNOWFLAKE_ID_KEY, CORRELATION_PRETTY_ID_KEY, CORRELATION_SNOWFLAKE_ID_KEY,
+    MESSAGE_PRETTY_ID_KEY, MESSAGE_SNOWFLAKE_ID_KEY, PARENT_SNOWFLAKE_IDS_KEY, RECV_TIMESTAMP_KEY, TRACEPARENT_KEY,
+    TRACESTATE_KEY,
+};
+use crate::envelope::{MetaData, MetaDataError, Strictness, TryIntoMetaData};
+
+#[derive(Debug, Error)]
+pub enum MetaDataCodecError {
+    #[error("Invalid header {key:?}: {reason}")]
+    InvalidHeader { key: String, reason: String },
+
+    #[error("Header {0:?} is not valid UTF-8")]
+    InvalidUtf8(String),
+
+    #[error(transparent)]
+    MetaData(#[from] MetaDataError),
+}
+
+/// Writes metadata into, and reads metadata from, `C`, the headers or properties of a transport's
+/// messages.
+///
+/// Decoding is strict, as with [`Strictness::Strict`]: a missing or malformed correlation id or
+/// receipt timestamp, or any other malformed metadata header, fails with
+/// [`MetaDataCodecError::MetaData`] rather than minting a new correlation id. Decoded metadata is
+/// correlated to `()`, and may be relabeled for the message content.
+pub trait MetaDataCodec<C> {
+    fn encode<T>(&self, metadata: &MetaData<T>, carrier: &mut C) -> Result<(), MetaDataCodecError>;
+    fn decode(&self, carrier: &C) -> Result<MetaData<()>, MetaDataCodecError>;
+}
+
+/// The header keys metadata fields are carried under.
+///
+/// The default keys match those of `HashMap<String, String>` conversions of [`MetaData`]. Custom
+/// metadata entries are carried under their key prefixed by `custom_prefix`, by default `meta.`; on
+/// decoding, headers without the prefix are ignored, so that unrelated headers, which may not be
+/// UTF-8, pass by. With an empty prefix, every other header is taken as custom metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaDataKeys {
+    pub correlation_id: String,
+    pub correlation_pretty_id: String,
+    pub recv_timestamp: String,
+    pub message_id: String,
+    pub message_pretty_id: String,
+    pub causation_id: String,
+    pub causation_pretty_id: String,
+    pub parent_ids: String,
+    pub traceparent: String,
+    pub tracestate: String,
+    pub custom_prefix: String,
+}
+
+impl Default for MetaDataKeys {
+    fn default() -> Self {
+        Self {
+            correlation_id: CORRELATION_SNOWFLAKE_ID_KEY.clone(),
+            correlation_pretty_id: CORRELATION_PRETTY_ID_KEY.clone(),
+            recv_timestamp: RECV_TIMESTAMP_KEY.clone(),
+            message_id: MESSAGE_SNOWFLAKE_ID_KEY.clone(),
+            message_pretty_id: MESSAGE_PRETTY_ID_KEY.clone(),
+            causation_id: CAUSATION_SNOWFLAKE_ID_KEY.clone(),
+            causation_pretty_id: CAUSATION_PRETTY_ID_KEY.clone(),
+            parent_ids: PARENT_SNOWFLAKE_IDS_KEY.clone(),
+            traceparent: TRACEPARENT_KEY.clone(),
+            tracestate: TRACESTATE_KEY.clone(),
+            custom_prefix: "meta.".to_string(),
+        }
+    }
+}
+
+impl MetaDataKeys {
+    /// Conventional HTTP header names; e.g., `X-Correlation-Id`. Custom metadata is carried under
+    /// `X-Meta-` headers.
+    pub fn http() -> Self {
+        Self {
+            correlation_id: "X-Correlation-Id".to_string(),
+            correlation_pretty_id: "X-Correlation-Pretty-Id".to_string(),
+            recv_timestamp: "X-Recv-Timestamp".to_string(),
+            message_id: "X-Message-Id".to_string(),
+            message_pretty_id: "X-Message-Pretty-Id".to_string(),
+            causation_id: "X-Causation-Id".to_string(),
+            causation_pretty_id: "X-Causation-Pretty-Id".to_string(),
+            parent_ids: "X-Parent-Ids".to_string(),
+            custom_prefix: "X-Meta-".to_string(),
+            ..Self::default()
+        }
+    }
+
+    // Pairs the keys of `HashMap<String, String>` conversions with the configured keys.
+    fn mappings(&self) -> [(&'static str, &str); 10] {
+        [
+            (CORRELATION_SNOWFLAKE_ID_KEY.deref(), &self.correlation_id),
+            (CORRELATION_PRETTY_ID_KEY.deref(), &self.correlation_pretty_id),
+            (RECV_TIMESTAMP_KEY.deref(), &self.recv_timestamp),
+            (MESSAGE_SNOWFLAKE_ID_KEY.deref(), &self.message_id),
+            (MESSAGE_PRETTY_ID_KEY.deref(), &self.message_pretty_id),
+            (CAUSATION_SNOWFLAKE_ID_KEY.deref(), &self.causation_id),
+            (CAUSATION_PRETTY_ID_KEY.deref(), &self.causation_pretty_id),
+            (PARENT_SNOWFLAKE_IDS_KEY.deref(), &self.parent_ids),
+            (TRACEPARENT_KEY.deref(), &self.traceparent),
+            (TRACESTATE_KEY.deref(), &self.tracestate),
+        ]
+    }
+
+    /// Maps metadata onto header entries.
+    fn encode_entries<T>(&self, metadata: &MetaData<T>) -> Vec<(String, String)> {
+        let mut entries: HashMap<String, String> = metadata.clone().into();
+        let mut headers = Vec::with_capacity(entries.len());
+        for (entry_key, key) in self.mappings() {
+            if let Some(value) = entries.remove(entry_key) {
+                headers.push((key.to_string(), value));
+            }
+        }
+
+        headers.extend(
+            entries
+                .into_iter()
+                .map(|(key, value)| (format!("{}{key}", self.custom_prefix), value)),
+        );
+        headers
+    }
+
+    /// Maps header entries back onto metadata. Header keys are matched ignoring ASCII case, since
+    /// some transports, such as HTTP, normalize their case. Only the values of headers carrying
+    /// metadata are converted via `to_string`, so that unrelated headers cannot fail decoding.
+    fn decode_entries<'h, V>(
+        &self, headers: impl IntoIterator<Item = (&'h str, V)>,
+        to_string: impl Fn(&'h str, V) -> Result<String, MetaDataCodecError>,
+    ) -> Result<MetaData<()>, MetaDataCodecError> {
+        let mappings = self.mappings();
+        let mut entries = HashMap::new();
+        for (key, value) in headers {
+            let entry_key = mappings
+                .iter()
+                .find_map(|(entry_key, k)| k.eq_ignore_ascii_case(key).then_some(*entry_key))
+                .or_else(|| strip_prefix_ignore_ascii_case(key, &self.custom_prefix));
+
+            if let Some(entry_key) = entry_key {
+                entries.insert(entry_key.to_string(), to_string(key, value)?);
+            }
+        }
+
+        Ok(entries.try_into_metadata(Strictness::Strict)?)
+    }
+}
+
+fn strip_prefix_ignore_ascii_case<'k>(key: &'k str, prefix: &str) -> Option<&'k str> {
+    let has_prefix = key.get(..prefix.len()).is_some_and(|p| p.eq_ignore_ascii_case(prefix));
+    has_prefix.then(|| &key[prefix.len()..])
+}
+
+/// Maps metadata onto HTTP headers, by default under conventional names such as
+/// `X-Correlation-Id`.
+#[cfg(feature = "http")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpHeaderCodec {
+    pub keys: MetaDataKeys,
+}
+
+#[cfg(feature = "http")]
+impl Default for HttpHeaderCodec {
+    fn default() -> Self {
+        Self { keys: MetaDataKeys::http() }
+    }
+}
+
+#[cfg(feature = "http")]
+impl MetaDataCodec<http::HeaderMap> for HttpHeaderCodec {
+    fn encode<T>(&self, metadata: &MetaData<T>, carrier: &mut http::HeaderMap) -> Result<(), MetaDataCodecError> {
+        for (key, value) in self.keys.encode_entries(metadata) {
+            let invalid = |reason: String| MetaDataCodecError::InvalidHeader { key: key.clone(), reason };
+            let name = http::HeaderName::try_from(key.as_str()).map_err(|err| invalid(err.to_string()))?;
+            let value = http::HeaderValue::try_from(value).map_err(|err| invalid(err.to_string()))?;
+            carrier.insert(name, value);
+        }
+        Ok(())
+    }
+
+    fn decode(&self, carrier: &http::HeaderMap) -> Result<MetaData<()>, MetaDataCodecError> {
+        let headers = carrier.iter().map(|(name, value)| (name.as_str(), value));
+        self.keys.decode_entries(headers, |key, value| {
+            value
+                .to_str()
+                .map(ToString::to_string)
+                .map_err(|err| MetaDataCodecError::InvalidHeader { key: key.to_string(), reason: err.to_string() })
+        })
+    }
+}
+
+/// The record headers of a Kafka message, such as those of a Kafka client's records.
+pub trait KafkaHeaders {
+    /// The headers of the record, in order. Headers without a value are omitted.
+    fn headers(&self) -> Vec<(&str, &[u8])>;
+
+    fn insert_header(&mut self, key: &str, value: Vec<u8>);
+}
+
+impl KafkaHeaders for Vec<(String, Vec<u8>)> {
+    fn headers(&self) -> Vec<(&str, &[u8])> {
+        self.iter().map(|(key, value)| (key.as_str(), value.as_slice())).collect()
+    }
+
+    fn insert_header(&mut self, key: &str, value: Vec<u8>) {
+        self.push((key.to_string(), value));
+    }
+}
+
+/// Maps metadata onto Kafka record headers, with UTF-8 encoded values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KafkaHeaderCodec {
+    pub keys: MetaDataKeys,
+}
+
+impl<H: KafkaHeaders> MetaDataCodec<H> for KafkaHeaderCodec {
+    fn encode<T>(&self, metadata: &MetaData<T>, carrier: &mut H) -> Result<(), MetaDataCodecError> {
+        for (key, value) in self.keys.encode_entries(metadata) {
+            carrier.insert_header(&key, value.into_bytes());
+        }
+        Ok(())
+    }
+
+    fn decode(&self, carrier: &H) -> Result<MetaData<()>, MetaDataCodecError> {
+        self.keys.decode_entries(carrier.headers(), |key, value| {
+            std::str::from_utf8(value)
+                .map(ToString::to_string)
+                .map_err(|_| MetaDataCodecError::InvalidUtf8(key.to_string()))
+        })
+    }
+}
+
+/// The properties of an AMQP message, such as those of an AMQP client's published messages.
+pub trait AmqpProperties {
+    fn correlation_id(&self) -> Option<&str>;
+    fn set_correlation_id(&mut self, correlation_id: String);
+    fn message_id(&self) -> Option<&str>;
+    fn set_message_id(&mut self, message_id: String);
+
+    /// The string-valued entries of the headers table.
+    fn headers(&self) -> Vec<(&str, &str)>;
+
+    fn insert_header(&mut self, key: &str, value: String);
+}
+
+/// Maps metadata onto AMQP message properties. The correlation and message snowflake ids are
+/// carried in the `correlation-id` and `message-id` properties, and the remaining metadata in the
+/// headers table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AmqpPropertiesCodec {
+    pub keys: MetaDataKeys,
+}
+
+impl<P: AmqpProperties> MetaDataCodec<P> for AmqpPropertiesCodec {
+    fn encode<T>(&self, metadata: &MetaData<T>, carrier: &mut P) -> Result<(), MetaDataCodecError> {
+        for (key, value) in self.keys.encode_entries(metadata) {
+            if key == self.keys.correlation_id {
+                carrier.set_correlation_id(value);
+            } else if key == self.keys.message_id {
+                carrier.set_message_id(value);
+            } else {
+                carrier.insert_header(&key, value);
+            }
+        }
+        Ok(())
+    }
+
+    fn decode(&self, carrier: &P) -> Result<MetaData<()>, MetaDataCodecError> {
+        let properties = [
+            (self.keys.correlation_id.as_str(), carrier.correlation_id()),
+            (self.keys.message_id.as_str(), carrier.message_id()),
+        ];
+
+        let headers = properties
+            .into_iter()
+            .filter_map(|(key, value)| value.map(|v| (key, v)))
+            .chain(carrier.headers());
+
+        self.keys.decode_entries(headers, |_, value| Ok(value.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::*;
+    use iso8601_timestamp::Timestamp;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::envelope::{Correlation, ReceivedAt, TraceContext};
+    use crate::{generator, Id};
+
+    // Headers carry the receipt timestamp at millisecond precision.
+    const RECV_TS: &str = "2022-11-30T03:43:18.068Z";
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    // Stands in for a Kafka client's record headers, which may hold headers without values.
+    #[derive(Debug, Default)]
+    struct RecordHeaders(Vec<(String, Option<Vec<u8>>)>);
+
+    impl KafkaHeaders for RecordHeaders {
+        fn headers(&self) -> Vec<(&str, &[u8])> {
+            self.0
+                .iter()
+                .filter_map(|(key, value)| value.as_deref().map(|v| (key.as_str(), v)))
+                .collect()
+        }
+
+        fn insert_header(&mut self, key: &str, value: Vec<u8>) {
+            self.0.push((key.to_string(), Some(value)));
+        }
+    }
+
+    // Stands in for an AMQP client's basic properties.
+    #[derive(Debug, Default)]
+    struct BasicProperties {
+        correlation_id: Option<String>,
+        message_id: Option<String>,
+        headers: Vec<(String, String)>,
+    }
+
+    impl AmqpProperties for BasicProperties {
+        fn correlation_id(&self) -> Option<&str> {
+            self.correlation_id.as_deref()
+        }
+
+        fn set_correlation_id(&mut self, correlation_id: String) {
+            self.correlation_id = Some(correlation_id);
+        }
+
+        fn message_id(&self) -> Option<&str> {
+            self.message_id.as_deref()
+        }
+
+        fn set_message_id(&mut self, message_id: String) {
+            self.message_id = Some(message_id);
+        }
+
+        fn headers(&self) -> Vec<(&str, &str)> {
+            self.headers.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect()
+        }
+
+        fn insert_header(&mut self, key: &str, value: String) {
+            self.headers.push((key.to_string(), value));
+        }
+    }
+
+    fn metadata() -> MetaData<()> {
+        let mut custom = HashMap::new();
+        custom.insert("cat".to_string(), "Otis".to_string());
+        let root = MetaData::<()>::from_parts(generator::next_id(), Timestamp::now_utc(), None);
+        let cause = MetaData::<()>::caused_by(&root);
+
+        let trace_context = assert_ok!(TraceContext::parse(TRACEPARENT, Some("rojo=00f067aa0ba902b7")));
+        let metadata = MetaData::<()>::caused_by(&cause).with_trace_context(Some(trace_context));
+        let (correlation_id, _, _) = metadata.clone().into_parts();
+        let recv_timestamp = assert_some!(Timestamp::parse(RECV_TS));
+        MetaData::from_parts(correlation_id, recv_timestamp, Some(custom))
+            .with_message_id(metadata.message_id().clone())
+            .with_lineage(metadata.causation_id().cloned(), metadata.parent_ids().to_vec())
+            .with_trace_context(metadata.trace_context().cloned())
+    }
+
+    fn assert_round_trip(expected: &MetaData<()>, actual: &MetaData<()>) {
+        let nums = |ids: &[Id<()>]| ids.iter().map(Id::num).collect::<Vec<_>>();
+        assert_eq!(actual.correlation().num(), expected.correlation().num());
+        assert_eq!(actual.correlation().pretty(), expected.correlation().pretty());
+        assert_eq!(actual.message_id().num(), expected.message_id().num());
+        assert_eq!(actual.causation_id().map(Id::num), expected.causation_id().map(Id::num));
+        assert_eq!(nums(actual.parent_ids()), nums(expected.parent_ids()));
+        assert_eq!(actual.recv_timestamp(), expected.recv_timestamp());
+        assert_eq!(actual.trace_context(), expected.trace_context());
+        assert_eq!(actual.clone().into_parts().2, expected.clone().into_parts().2);
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_http_round_trip() {
+        let expected = metadata();
+        let codec = HttpHeaderCodec::default();
+        let mut headers = http::HeaderMap::new();
+        headers.insert(
+            http::header::CONTENT_TYPE,
+            http::HeaderValue::from_static("application/json"),
+        );
+        assert_ok!(codec.encode(&expected, &mut headers));
+
+        assert_eq!(
+            headers.get("X-Correlation-Id").and_then(|v| v.to_str().ok()),
+            Some(expected.correlation().num().to_string().as_str())
+        );
+        assert_eq!(headers.get("x-meta-cat").and_then(|v| v.to_str().ok()), Some("Otis"));
+        assert_eq!(
+            headers.get("traceparent").and_then(|v| v.to_str().ok()),
+            Some(TRACEPARENT)
+        );
+
+        let actual = assert_ok!(codec.decode(&headers));
+        assert_round_trip(&expected, &actual);
+
+        headers.insert("x-unrelated", assert_ok!(http::HeaderValue::from_bytes(&[0xff])));
+        let actual = assert_ok!(codec.decode(&headers));
+        assert_round_trip(&expected, &actual);
+
+        headers.insert("x-meta-dog", assert_ok!(http::HeaderValue::from_bytes(&[0xff])));
+        let err = assert_err!(codec.decode(&headers));
+        assert!(matches!(err, MetaDataCodecError::InvalidHeader { key, .. } if key == "x-meta-dog"));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn test_http_invalid_header() {
+        let mut custom = HashMap::new();
+        custom.insert("bad key".to_string(), "value".to_string());
+        let metadata = MetaData::<()>::from_parts(generator::next_id(), Timestamp::now_utc(), Some(custom));
+
+        let err = assert_err!(HttpHeaderCodec::default().encode(&metadata, &mut http::HeaderMap::new()));
+        assert!(matches!(err, MetaDataCodecError::InvalidHeader { key, .. } if key == "X-Meta-bad key"));
+    }
+
+    #[test]
+    fn test_kafka_round_trip() {
+        let expected = metadata();
+        let codec = KafkaHeaderCodec::default();
+        let mut headers = RecordHeaders(vec![("tombstone".to_string(), None)]);
+        assert_ok!(codec.encode(&expected, &mut headers));
+
+        let actual = assert_ok!(codec.decode(&headers));
+        assert_round_trip(&expected, &actual);
+
+        headers.insert_header("schema", vec![0xff]);
+        let actual = assert_ok!(codec.decode(&headers));
+        assert_round_trip(&expected, &actual);
+
+        headers.insert_header("meta.dog", vec![0xff]);
+        let err = assert_err!(MetaDataCodec::<RecordHeaders>::decode(&codec, &headers));
+        assert!(matches!(err, MetaDataCodecError::InvalidUtf8(key) if key == "meta.dog"));
+    }
+
+    #[test]
+    fn test_decode_is_strict() {
+        let codec = KafkaHeaderCodec::default();
+        let mut headers: Vec<(String, Vec<u8>)> = vec![("recv_timestamp".to_string(), RECV_TS.as_bytes().to_vec())];
+        let err = assert_err!(codec.decode(&headers));
+        assert!(matches!(
+            err,
+            MetaDataCodecError::MetaData(MetaDataError::MissingField(key)) if key == "correlation_snowflake_id"
+        ));
+
+        headers.push(("correlation_snowflake_id".to_string(), b"not a number".to_vec()));
+        let err = assert_err!(codec.decode(&headers));
+        assert!(matches!(
+            err,
+            MetaDataCodecError::MetaData(MetaDataError::MalformedField { key, .. }) if key == "correlation_snowflake_id"
+        ));
+    }
+
+    #[test]
+    fn test_amqp_round_trip() {
+        let expected = metadata();
+        let codec = AmqpPropertiesCodec::default();
+        let mut properties = BasicProperties::default();
+        assert_ok!(codec.encode(&expected, &mut properties));
+
+        assert_eq!(
+            properties.correlation_id,
+            Some(expected.correlation().num().to_string())
+        );
+        assert_eq!(properties.message_id, Some(expected.message_id().num().to_string()));
+        assert!(properties.headers.iter().all(|(key, _)| key != "correlation_snowflake_id"));
+
+        let actual = assert_ok!(codec.decode(&properties));
+        assert_round_trip(&expected, &actual);
+    }
+
+    #[test]
+    fn test_configured_keys() {
+        let expected = metadata();
+        let keys = MetaDataKeys {
+            correlation_id: "corr".to_string(),
+            recv_timestamp: "ts".to_string(),
+            custom_prefix: "meta.".to_string(),
+            ..MetaDataKeys::default()
+        };
+        let codec = KafkaHeaderCodec { keys };
+        let mut headers: Vec<(String, Vec<u8>)> = Vec::new();
+        assert_ok!(codec.encode(&expected, &mut headers));
+
+        let keys: Vec<_> = headers.iter().map(|(key, _)| key.as_str()).collect();
+        assert!(keys.contains(&"corr"));
+        assert!(keys.contains(&"ts"));
+        assert!(keys.contains(&"meta.cat"));
+        assert!(!keys.contains(&"correlation_snowflake_id"));
+
+        headers.push(("unrelated".to_string(), b"ignored".to_vec()));
+        headers.push(("binary".to_string(), vec![0xff]));
+        let actual = assert_ok!(codec.decode(&headers));
+        assert_round_trip(&expected, &actual);
+
+        headers.push(("ts".to_string(), vec![0xff]));
+        let err = assert_err!(codec.decode(&headers));
+        assert!(matches!(err, MetaDataCodecError::InvalidUtf8(key) if key == "ts"));
+    }
+}
@@ -1,9 +1,9 @@
-use crate::{Id, Label};
+use crate::{Id, Label, RelabelFrom};
 use iso8601_timestamp::Timestamp;
 use std::fmt;
 use std::future::Future;
 
-use crate::envelope::{Correlation, MetaData, ReceivedAt};
+use crate::envelope::{Correlation, MergeError, MergePolicy, MetaData, ReceivedAt};
 #[cfg(feature = "functional")]
 use frunk::{Monoid, Semigroup};
 
@@ -51,6 +51,15 @@ where
         Self { metadata: MetaData::default(), content }
     }
 
+    /// Create enveloped data for a new message caused by the message of `cause`, linking their
+    /// lineage via [`MetaData::caused_by`].
+    pub fn caused_by<U: Label>(content: T, cause: &Envelope<U>) -> Self {
+        Self {
+            metadata: MetaData::caused_by(&cause.metadata),
+            content,
+        }
+    }
+
     /// Directly create enveloped data with given metadata.
     pub const fn direct(content: T, metadata: MetaData<T>) -> Self {
         Self { metadata, content }
@@ -61,6 +70,12 @@ where
         &self.metadata
     }
 
+    /// Opens a span for handling the enveloped message; see [`MetaData::span`].
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> tracing::Span {
+        self.metadata.span()
+    }
+
     /// Consumes self, returning the data item
     #[allow(clippy::missing_const_for_fn)]
     #[inline]
@@ -79,42 +94,197 @@ where
         Self { metadata, content }
     }
 
+    /// Merges two envelopes, merging their metadata according to `policy` and combining their
+    /// contents via `f`.
+    pub fn merge_with<F>(self, other: Self, policy: MergePolicy, f: F) -> Result<Self, MergeError>
+    where
+        F: FnOnce(T, T) -> T,
+    {
+        let metadata = self.metadata.merge(other.metadata, policy)?;
+        Ok(Self::from_parts(metadata, f(self.content, other.content)))
+    }
+
+    /// Merges two envelopes, merging their metadata according to `policy` and adding their
+    /// contents.
+    pub fn merge(self, other: Self, policy: MergePolicy) -> Result<Self, MergeError>
+    where
+        T: std::ops::Add<Output = T>,
+    {
+        self.merge_with(other, policy, std::ops::Add::add)
+    }
+
+    /// Replaces the metadata, relabeling `new_metadata` for the content and returning the previous
+    /// metadata.
     pub fn adopt_metadata<U>(&mut self, new_metadata: MetaData<U>) -> MetaData<T>
     where
         U: Label,
     {
         let old_metadata = self.metadata.clone();
-        self.metadata = new_metadata.relabel();
+        self.metadata = new_metadata.unchecked_relabel();
         old_metadata
     }
 
+    /// Maps the content, carrying the metadata along to the new content.
+    ///
+    /// The correlation id of an envelope identifies its message flow rather than its content, so
+    /// the metadata is relabeled for any new content type via [`MetaData::unchecked_relabel`].
+    /// [`Envelope::checked_map`] instead requires the new type to declare the conversion via
+    /// [`RelabelFrom`].
     pub fn map<F, U>(self, f: F) -> Envelope<U>
     where
         U: Label + Send,
         F: FnOnce(T) -> U,
+    {
+        let metadata = self.metadata.clone().unchecked_relabel();
+        Envelope { metadata, content: f(self.content) }
+    }
+
+    /// Maps the content to a type declaring the conversion via [`RelabelFrom`], carrying the metadata
+    /// along to the new content via [`MetaData::relabel`].
+    pub fn checked_map<F, U>(self, f: F) -> Envelope<U>
+    where
+        U: Label + Send + RelabelFrom<T>,
+        F: FnOnce(T) -> U,
     {
         let metadata = self.metadata.clone().relabel();
         Envelope { metadata, content: f(self.content) }
     }
 
+    /// Maps the envelope into new content, carrying the metadata along unchecked, as
+    /// [`Envelope::map`] does.
     pub fn flat_map<F, U>(self, f: F) -> Envelope<U>
     where
         U: Label + Send,
         F: FnOnce(Self) -> U,
+    {
+        let metadata = self.metadata.clone().unchecked_relabel();
+        Envelope { metadata, content: f(self) }
+    }
+
+    /// Maps the envelope into new content of a type declaring the conversion via [`RelabelFrom`],
+    /// as [`Envelope::checked_map`] does.
+    pub fn checked_flat_map<F, U>(self, f: F) -> Envelope<U>
+    where
+        U: Label + Send + RelabelFrom<T>,
+        F: FnOnce(Self) -> U,
     {
         let metadata = self.metadata.clone().relabel();
         Envelope { metadata, content: f(self) }
     }
 
+    /// Maps the content via an asynchronous function, carrying the metadata along unchecked, as
+    /// [`Envelope::map`] does.
     pub async fn and_then<Op, Fut, U>(self, f: Op) -> Envelope<U>
     where
         U: Label + Send,
         Fut: Future<Output = U> + Send,
         Op: FnOnce(T) -> Fut + Send,
+    {
+        let metadata = self.metadata.clone().unchecked_relabel();
+        Envelope { metadata, content: f(self.content).await }
+    }
+
+    /// Maps the content via an asynchronous function to a type declaring the conversion via
+    /// [`RelabelFrom`], as [`Envelope::checked_map`] does.
+    pub async fn checked_and_then<Op, Fut, U>(self, f: Op) -> Envelope<U>
+    where
+        U: Label + Send + RelabelFrom<T>,
+        Fut: Future<Output = U> + Send,
+        Op: FnOnce(T) -> Fut + Send,
     {
         let metadata = self.metadata.clone().relabel();
         Envelope { metadata, content: f(self.content).await }
     }
+
+    /// Maps the content via a fallible function. Unlike [`Envelope::transpose`], the metadata is
+    /// kept on failure as well, in [`Failed`], so the error can still be correlated; e.g., when
+    /// dead-lettering it.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use pretty_snowflake::envelope::{Correlation, Envelope};
+    ///
+    /// let envelope = Envelope::new("seventeen".to_string());
+    /// let correlation_id = envelope.correlation().num();
+    /// let failed = envelope.try_map(|s| s.parse::<i32>()).unwrap_err();
+    /// assert_eq!(failed.metadata.correlation().num(), correlation_id);
+    /// assert_eq!(failed.error, "seventeen".parse::<i32>().unwrap_err());
+    /// ```
+    #[allow(clippy::result_large_err)]
+    pub fn try_map<F, U, E>(self, f: F) -> Result<Envelope<U>, Failed<E>>
+    where
+        U: Label + Send,
+        F: FnOnce(T) -> Result<U, E>,
+    {
+        let (metadata, content) = self.into_parts();
+        match f(content) {
+            Ok(content) => Ok(Envelope { metadata: metadata.unchecked_relabel(), content }),
+            Err(error) => Err(Failed::new(metadata, error)),
+        }
+    }
+
+    /// Maps the content via a fallible asynchronous function, keeping the metadata on failure as
+    /// well; see [`Envelope::try_map`].
+    #[allow(clippy::result_large_err)]
+    pub async fn try_and_then<Op, Fut, U, E>(self, f: Op) -> Result<Envelope<U>, Failed<E>>
+    where
+        U: Label + Send,
+        Fut: Future<Output = Result<U, E>> + Send,
+        Op: FnOnce(T) -> Fut + Send,
+    {
+        let (metadata, content) = self.into_parts();
+        match f(content).await {
+            Ok(content) => Ok(Envelope { metadata: metadata.unchecked_relabel(), content }),
+            Err(error) => Err(Failed::new(metadata, error)),
+        }
+    }
+}
+
+/// A failed [`Envelope::try_map`] or [`Envelope::try_and_then`], with the envelope's metadata.
+///
+/// The metadata is correlated to `()`, since errors need not be labeled; it may be relabeled, e.g.,
+/// via [`Failed::into_envelope`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failed<E> {
+    pub metadata: MetaData<()>,
+    pub error: E,
+}
+
+impl<E> Failed<E> {
+    fn new<T>(metadata: MetaData<T>, error: E) -> Self {
+        Self { metadata: metadata.unchecked_relabel(), error }
+    }
+
+    /// Wraps the error in an envelope carrying the metadata; e.g., for a dead-letter queue.
+    pub fn into_envelope(self) -> Envelope<E>
+    where
+        E: Label,
+    {
+        Envelope {
+            metadata: self.metadata.unchecked_relabel(),
+            content: self.error,
+        }
+    }
+}
+
+impl<E> fmt::Display for Failed<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to map envelope content correlated by {}",
+            self.metadata.correlation()
+        )
+    }
+}
+
+impl<E> std::error::Error for Failed<E>
+where
+    E: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
 }
 
 impl<T> Correlation for Envelope<T>
@@ -146,8 +316,18 @@ where
     fn labeler() -> Self::Labeler {
         <T as Label>::labeler()
     }
+
+    fn label() -> &'static str {
+        <T as Label>::label()
+    }
+
+    fn prefix() -> Option<&'static str> {
+        <T as Label>::prefix()
+    }
 }
 
+/// Adds the contents, keeping the metadata received latest; i.e., merges via
+/// [`MergePolicy::KeepLatest`]. Use [`Envelope::merge`] for another policy.
 impl<T> std::ops::Add for Envelope<T>
 where
     T: std::ops::Add<Output = T> + Label + Send,
@@ -169,6 +349,7 @@ where
     }
 }
 
+/// Combines the contents, keeping the metadata received latest, as `Add` does.
 #[cfg(feature = "functional")]
 impl<T> Semigroup for Envelope<T>
 where
@@ -0,0 +1,324 @@
+use std::any::Any;
+use std::collections::HashMap;
+use std::fmt;
+
+use once_cell::sync::OnceCell;
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::{Error as _, SerializeMap};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+/// A typed value carried in the [`Extensions`] of envelope metadata; e.g., a tenant id or a
+/// priority.
+///
+/// Extensions are looked up by type, and serialized under their registered `KEY`, so the key must
+/// be unique among the extensions used together and stable across the services exchanging them.
+pub trait Extension: Serialize + DeserializeOwned + Clone + fmt::Debug + Send + Sync + 'static {
+    const KEY: &'static str;
+}
+
+trait AnyExtension: Any + Send + Sync + fmt::Debug {
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+    fn clone_box(&self) -> Box<dyn AnyExtension>;
+    fn to_value(&self) -> serde_json::Result<Value>;
+}
+
+impl<E: Extension> AnyExtension for E {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn clone_box(&self) -> Box<dyn AnyExtension> {
+        Box::new(self.clone())
+    }
+
+    fn to_value(&self) -> serde_json::Result<Value> {
+        serde_json::to_value(self)
+    }
+}
+
+enum Slot {
+    Typed(Box<dyn AnyExtension>),
+
+    /// A deserialized extension, whose type is not known until it is first accessed.
+    Serialized {
+        value: Value,
+        typed: OnceCell<Box<dyn AnyExtension>>,
+    },
+}
+
+impl Slot {
+    fn serialized(value: Value) -> Self {
+        Self::Serialized { value, typed: OnceCell::new() }
+    }
+
+    fn get<E: Extension>(&self) -> Option<&E> {
+        let typed = match self {
+            Self::Typed(typed) => typed,
+            Self::Serialized { value, typed } => typed
+                .get_or_try_init(|| {
+                    serde_json::from_value::<E>(value.clone()).map(|e| Box::new(e) as Box<dyn AnyExtension>)
+                })
+                .ok()?,
+        };
+        (**typed).as_any().downcast_ref()
+    }
+
+    fn into_typed<E: Extension>(self) -> Option<Box<dyn AnyExtension>> {
+        match self {
+            Self::Typed(typed) => Some(typed),
+            Self::Serialized { value, typed } => typed.into_inner().or_else(|| {
+                serde_json::from_value::<E>(value)
+                    .ok()
+                    .map(|e| Box::new(e) as Box<dyn AnyExtension>)
+            }),
+        }
+    }
+
+    fn to_value(&self) -> serde_json::Result<Value> {
+        match self {
+            Self::Typed(typed) => typed.to_value(),
+            Self::Serialized { value, .. } => Ok(value.clone()),
+        }
+    }
+}
+
+impl Clone for Slot {
+    #[allow(clippy::option_if_let_else)]
+    fn clone(&self) -> Self {
+        match self {
+            Self::Typed(typed) => Self::Typed(typed.clone_box()),
+            Self::Serialized { value, typed } => match typed.get() {
+                Some(typed) => Self::Typed(typed.clone_box()),
+                None => Self::serialized(value.clone()),
+            },
+        }
+    }
+}
+
+impl fmt::Debug for Slot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Typed(typed) => typed.fmt(f),
+            Self::Serialized { value, .. } => value.fmt(f),
+        }
+    }
+}
+
+/// A typed extension map of envelope metadata, in the spirit of `http::Extensions`, holding at most
+/// one value of each [`Extension`] type.
+///
+/// Extensions serialize as a map from their keys to their values. Deserialized values are
+/// converted into their types upon first access via [`Extensions::get`], so extensions of types
+/// unknown to a service survive it unchanged.
+///
+/// Since deserialized values are held as JSON values until then, in formats that are not
+/// human-readable, such as bincode, each value is carried as a JSON string.
+#[derive(Default, Clone)]
+pub struct Extensions {
+    map: HashMap<String, Slot>,
+}
+
+impl fmt::Debug for Extensions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.map.iter()).finish()
+    }
+}
+
+impl Extensions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get<E: Extension>(&self) -> Option<&E> {
+        self.map.get(E::KEY)?.get()
+    }
+
+    pub fn get_mut<E: Extension>(&mut self) -> Option<&mut E> {
+        let slot = self.map.get_mut(E::KEY)?;
+        if let Slot::Serialized { .. } = slot {
+            if let Some(typed) = slot.clone().into_typed::<E>() {
+                *slot = Slot::Typed(typed);
+            }
+        }
+
+        match slot {
+            Slot::Typed(typed) => (**typed).as_any_mut().downcast_mut(),
+            Slot::Serialized { .. } => None,
+        }
+    }
+
+    /// Inserts an extension, returning the previous extension of its type, if any.
+    pub fn insert<E: Extension>(&mut self, extension: E) -> Option<E> {
+        self.map
+            .insert(E::KEY.to_string(), Slot::Typed(Box::new(extension)))
+            .and_then(|previous| previous.into_typed::<E>())
+            .and_then(|typed| typed.into_any().downcast().ok())
+            .map(|e| *e)
+    }
+
+    /// Removes the extension of type `E`. An extension of another type registered under the same
+    /// key is left in place.
+    pub fn remove<E: Extension>(&mut self) -> Option<E> {
+        self.get_mut::<E>()?;
+        match self.map.remove(E::KEY)? {
+            Slot::Typed(typed) => typed.into_any().downcast().ok().map(|e| *e),
+            Slot::Serialized { .. } => None,
+        }
+    }
+
+    /// The serialized value of the extension registered under `key`, such as for an extension
+    /// whose type is unknown to the service.
+    pub fn get_value(&self, key: &str) -> Option<Value> {
+        self.map.get(key)?.to_value().ok()
+    }
+
+    /// Inserts a serialized extension value under `key`, which is converted into its type upon
+    /// access.
+    pub fn insert_value(&mut self, key: impl Into<String>, value: Value) {
+        self.map.insert(key.into(), Slot::serialized(value));
+    }
+
+    /// Adds the extensions of `other` under keys missing from these.
+    pub(crate) fn union(&mut self, other: Self) {
+        for (key, slot) in other.map {
+            self.map.entry(key).or_insert(slot);
+        }
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.map.keys().map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+}
+
+// Values are held as JSON, which compact formats, such as bincode, may be unable to represent, as
+// they are not self-describing; there, values are carried as JSON strings instead.
+impl Serialize for Extensions {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let human_readable = serializer.is_human_readable();
+        let mut map = serializer.serialize_map(Some(self.map.len()))?;
+        for (key, slot) in &self.map {
+            let value = slot.to_value().map_err(S::Error::custom)?;
+            if human_readable {
+                map.serialize_entry(key, &value)?;
+            } else {
+                map.serialize_entry(key, &value.to_string())?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Extensions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let values = if deserializer.is_human_readable() {
+            HashMap::<String, Value>::deserialize(deserializer)?
+        } else {
+            HashMap::<String, String>::deserialize(deserializer)?
+                .into_iter()
+                .map(|(key, rep)| serde_json::from_str(&rep).map(|value| (key, value)))
+                .collect::<Result<_, _>>()
+                .map_err(D::Error::custom)?
+        };
+
+        let map = values
+            .into_iter()
+            .map(|(key, value)| (key, Slot::serialized(value)))
+            .collect();
+        Ok(Self { map })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::*;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct TenantId(String);
+
+    impl Extension for TenantId {
+        const KEY: &'static str = "tenant_id";
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    enum Priority {
+        Low,
+        High,
+    }
+
+    impl Extension for Priority {
+        const KEY: &'static str = "priority";
+    }
+
+    #[test]
+    fn test_typed_access() {
+        let mut extensions = Extensions::new();
+        assert_none!(extensions.insert(TenantId("acme".to_string())));
+        assert_none!(extensions.insert(Priority::Low));
+        assert_eq!(extensions.len(), 2);
+
+        assert_eq!(extensions.get::<TenantId>(), Some(&TenantId("acme".to_string())));
+        assert_eq!(extensions.insert(Priority::High), Some(Priority::Low));
+        *assert_some!(extensions.get_mut::<TenantId>()) = TenantId("globex".to_string());
+        assert_eq!(extensions.get_value(TenantId::KEY), Some(Value::from("globex")));
+
+        assert_eq!(extensions.remove::<Priority>(), Some(Priority::High));
+        assert_none!(extensions.get::<Priority>());
+        assert_eq!(extensions.keys().collect::<Vec<_>>(), vec![TenantId::KEY]);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let mut extensions = Extensions::new();
+        extensions.insert(TenantId("acme".to_string()));
+        extensions.insert(Priority::High);
+        extensions.insert_value("unknown", serde_json::json!({ "a": 1 }));
+
+        let json = assert_ok!(serde_json::to_string(&extensions));
+        let mut actual: Extensions = assert_ok!(serde_json::from_str(&json));
+        assert_eq!(actual.get::<TenantId>(), Some(&TenantId("acme".to_string())));
+        assert_eq!(actual.get::<Priority>(), Some(&Priority::High));
+        assert_eq!(actual.get_value("unknown"), Some(serde_json::json!({ "a": 1 })));
+
+        let json = assert_ok!(serde_json::to_string(&actual.clone()));
+        let again: Extensions = assert_ok!(serde_json::from_str(&json));
+        assert_eq!(again.len(), 3);
+        assert_eq!(actual.remove::<TenantId>(), Some(TenantId("acme".to_string())));
+    }
+
+    #[test]
+    fn test_mismatched_value_is_kept() {
+        let mut extensions = Extensions::new();
+        extensions.insert_value(Priority::KEY, Value::from(7));
+
+        assert_none!(extensions.get::<Priority>());
+        assert_none!(extensions.get_mut::<Priority>());
+        assert_none!(extensions.remove::<Priority>());
+        assert_eq!(extensions.get_value(Priority::KEY), Some(Value::from(7)));
+    }
+}
@@ -0,0 +1,341 @@
+//! Tamper detection for envelopes passing through untrusted intermediaries, via the `integrity`
+//! feature.
+//!
+//! Sealing an envelope records a SHA-256 digest of its content, and optionally an HMAC-SHA256
+//! signature, in the [`Seal`] extension of its metadata, so the seal travels wherever the metadata
+//! does. The digest is computed over a canonical JSON serialization of the content, with object
+//! keys in sorted order. The signature covers the digest along with the correlation and message
+//! ids, so a sealed content cannot be moved into another envelope undetected.
+
+use hmac::{Hmac, Mac};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::envelope::{Correlation, Envelope, Extension};
+use crate::Label;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const SHA256: &str = "sha256";
+
+#[derive(Debug, Error)]
+pub enum IntegrityError {
+    #[error("Envelope is not sealed")]
+    NotSealed,
+
+    #[error("Envelope is not signed")]
+    NotSigned,
+
+    #[error("Unsupported envelope digest algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+
+    #[error("Envelope content does not match its digest")]
+    DigestMismatch,
+
+    #[error("Envelope signature is invalid")]
+    InvalidSignature,
+
+    #[error("Failed to serialize envelope content: {0}")]
+    Serialization(#[from] serde_json::Error),
+}
+
+/// The digest and optional signature of a sealed envelope's content, carried as a metadata
+/// extension.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Seal {
+    algorithm: String,
+    digest: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+}
+
+impl Extension for Seal {
+    const KEY: &'static str = "seal";
+}
+
+impl Seal {
+    pub const fn algorithm(&self) -> &str {
+        self.algorithm.as_str()
+    }
+
+    /// The hex-encoded digest of the content.
+    pub const fn digest(&self) -> &str {
+        self.digest.as_str()
+    }
+
+    /// The hex-encoded signature, if the envelope was sealed with a key.
+    pub fn signature(&self) -> Option<&str> {
+        self.signature.as_deref()
+    }
+}
+
+impl<T> Envelope<T>
+where
+    T: Label + Send + Sync + Serialize,
+{
+    /// Seals the envelope with a digest of its content, replacing any previous seal.
+    pub fn seal(self) -> Result<Self, IntegrityError> {
+        let digest = content_digest(self.as_ref())?;
+        Ok(self.with_seal(Seal {
+            algorithm: SHA256.to_string(),
+            digest: encode_hex(&digest),
+            signature: None,
+        }))
+    }
+
+    /// Seals the envelope with a digest of its content and an HMAC signature under `key`,
+    /// replacing any previous seal.
+    pub fn seal_signed(self, key: &[u8]) -> Result<Self, IntegrityError> {
+        let digest = content_digest(self.as_ref())?;
+        let signature = self.signer(key, &digest).finalize().into_bytes();
+        Ok(self.with_seal(Seal {
+            algorithm: SHA256.to_string(),
+            digest: encode_hex(&digest),
+            signature: Some(encode_hex(&signature)),
+        }))
+    }
+
+    /// Checks that the content matches the digest of the envelope's seal. This detects accidental
+    /// modification only, since an intermediary may reseal modified content; see
+    /// [`Envelope::verify_signed`].
+    pub fn verify(&self) -> Result<(), IntegrityError> {
+        self.verify_digest().map(|_| ())
+    }
+
+    /// Checks that the content matches the digest of the envelope's seal, and that the seal was
+    /// signed under `key` for this envelope.
+    pub fn verify_signed(&self, key: &[u8]) -> Result<(), IntegrityError> {
+        let (seal, digest) = self.verify_digest()?;
+        let signature = seal.signature.as_deref().ok_or(IntegrityError::NotSigned)?;
+        let signature = decode_hex(signature).ok_or(IntegrityError::InvalidSignature)?;
+        self.signer(key, &digest)
+            .verify_slice(&signature)
+            .map_err(|_| IntegrityError::InvalidSignature)
+    }
+
+    /// The seal of the envelope, if any.
+    pub fn seal_of(&self) -> Option<&Seal> {
+        self.metadata().get::<Seal>()
+    }
+
+    fn verify_digest(&self) -> Result<(&Seal, Vec<u8>), IntegrityError> {
+        let seal = self.seal_of().ok_or(IntegrityError::NotSealed)?;
+        if seal.algorithm != SHA256 {
+            return Err(IntegrityError::UnsupportedAlgorithm(seal.algorithm.clone()));
+        }
+
+        let digest = content_digest(self.as_ref())?;
+        if decode_hex(&seal.digest).as_deref() != Some(digest.as_slice()) {
+            return Err(IntegrityError::DigestMismatch);
+        }
+        Ok((seal, digest))
+    }
+
+    fn signer(&self, key: &[u8], digest: &[u8]) -> HmacSha256 {
+        // HMAC accepts keys of any length.
+        let mut mac = HmacSha256::new_from_slice(key).expect("HMAC key of any length");
+        mac.update(digest);
+        mac.update(&self.correlation().num().to_be_bytes());
+        mac.update(&self.metadata().message_id().num().to_be_bytes());
+        mac
+    }
+
+    fn with_seal(self, seal: Seal) -> Self {
+        let (mut metadata, content) = self.into_parts();
+        metadata.insert(seal);
+        Self::from_parts(metadata, content)
+    }
+}
+
+/// Digests the canonical serialization of the content, so the digest depends neither on map
+/// iteration order nor on whether `serde_json` preserves the insertion order of object keys.
+fn content_digest<T: Serialize>(content: &T) -> Result<Vec<u8>, IntegrityError> {
+    let mut canonical = Vec::new();
+    write_canonical(&serde_json::to_value(content)?, &mut canonical)?;
+    Ok(Sha256::digest(canonical).to_vec())
+}
+
+/// Writes compact JSON with the keys of objects in sorted order.
+fn write_canonical(value: &Value, out: &mut Vec<u8>) -> serde_json::Result<()> {
+    match value {
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if 0 < i {
+                    out.push(b',');
+                }
+                write_canonical(item, out)?;
+            }
+            out.push(b']');
+        },
+
+        Value::Object(entries) => {
+            out.push(b'{');
+            for (i, (key, item)) in entries.iter().sorted_by_key(|(key, _)| *key).enumerate() {
+                if 0 < i {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut *out, key)?;
+                out.push(b':');
+                write_canonical(item, out)?;
+            }
+            out.push(b'}');
+        },
+
+        scalar => serde_json::to_writer(&mut *out, scalar)?,
+    }
+    Ok(())
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// `usize::is_multiple_of` is only stable since Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+fn decode_hex(rep: &str) -> Option<Vec<u8>> {
+    if rep.len() % 2 != 0 {
+        return None;
+    }
+
+    (0..rep.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(rep.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use claim::*;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::envelope::{IntoMetaData, MetaData};
+    use crate::MakeLabeling;
+
+    const KEY: &[u8] = b"shared secret";
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Order {
+        item: String,
+        quantity: u32,
+        tags: HashMap<String, String>,
+    }
+
+    impl Label for Order {
+        type Labeler = MakeLabeling<Self>;
+
+        fn labeler() -> Self::Labeler {
+            MakeLabeling::default()
+        }
+    }
+
+    fn order() -> Order {
+        let tags = (0..8).map(|i| (format!("tag{i}"), i.to_string())).collect();
+        Order { item: "widget".to_string(), quantity: 3, tags }
+    }
+
+    #[test]
+    fn test_seal_and_verify() {
+        let envelope = Envelope::new(order());
+        assert!(matches!(envelope.verify(), Err(IntegrityError::NotSealed)));
+
+        let sealed = assert_ok!(envelope.seal());
+        let seal = assert_some!(sealed.seal_of());
+        assert_eq!(seal.algorithm(), "sha256");
+        assert_eq!(seal.digest().len(), 64);
+        assert_none!(seal.signature());
+        assert_ok!(sealed.verify());
+        assert!(matches!(sealed.verify_signed(KEY), Err(IntegrityError::NotSigned)));
+
+        let tampered = sealed.map(|mut order| {
+            order.quantity = 300;
+            order
+        });
+        assert!(matches!(tampered.verify(), Err(IntegrityError::DigestMismatch)));
+    }
+
+    #[test]
+    fn test_digest_is_canonical() {
+        let a = assert_ok!(content_digest(&order()));
+        let b = assert_ok!(content_digest(&order()));
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_canonical_json_sorts_keys() {
+        let mut inner = serde_json::Map::new();
+        inner.insert("d".to_string(), Value::Null);
+        inner.insert("c".to_string(), Value::from(2));
+        let mut outer = serde_json::Map::new();
+        outer.insert(
+            "b".to_string(),
+            Value::Array(vec![Value::Object(inner), Value::from("x\"y")]),
+        );
+        outer.insert("a".to_string(), Value::from(1.5));
+
+        let mut canonical = Vec::new();
+        assert_ok!(write_canonical(&Value::Object(outer), &mut canonical));
+        assert_eq!(
+            String::from_utf8(canonical).unwrap(),
+            r#"{"a":1.5,"b":[{"c":2,"d":null},"x\"y"]}"#
+        );
+    }
+
+    #[test]
+    fn test_seal_signed() {
+        let sealed = assert_ok!(Envelope::new(order()).seal_signed(KEY));
+        assert_some!(assert_some!(sealed.seal_of()).signature());
+        assert_ok!(sealed.verify());
+        assert_ok!(sealed.verify_signed(KEY));
+        assert!(matches!(
+            sealed.verify_signed(b"another secret"),
+            Err(IntegrityError::InvalidSignature)
+        ));
+
+        // The content resealed by an intermediary without the key.
+        let (metadata, mut content) = assert_ok!(sealed.clone().seal()).into_parts();
+        content.quantity = 300;
+        let resealed = assert_ok!(Envelope::from_parts(metadata, content).seal());
+        assert_ok!(resealed.verify());
+        assert!(matches!(resealed.verify_signed(KEY), Err(IntegrityError::NotSigned)));
+
+        // The sealed content moved into another envelope along with its seal.
+        let (metadata, content) = sealed.into_parts();
+        let mut other = MetaData::<Order>::default();
+        other.insert(assert_some!(metadata.get::<Seal>()).clone());
+        let moved = Envelope::from_parts(other, content);
+        assert_ok!(moved.verify());
+        assert!(matches!(
+            moved.verify_signed(KEY),
+            Err(IntegrityError::InvalidSignature)
+        ));
+    }
+
+    #[test]
+    fn test_seal_survives_transport() {
+        let sealed = assert_ok!(Envelope::new(order()).seal_signed(KEY));
+
+        let json = assert_ok!(serde_json::to_string(&sealed));
+        let received: Envelope<Order> = assert_ok!(serde_json::from_str(&json));
+        assert_ok!(received.verify_signed(KEY));
+
+        let (metadata, content) = sealed.into_parts();
+        let map: HashMap<String, String> = metadata.into();
+        let received = Envelope::from_parts(map.into_metadata().unchecked_relabel(), content);
+        assert_ok!(received.verify_signed(KEY));
+    }
+
+    #[test]
+    fn test_hex() {
+        assert_eq!(encode_hex(&[0x00, 0x7f, 0xff]), "007fff");
+        assert_eq!(decode_hex("007fff"), Some(vec![0x00, 0x7f, 0xff]));
+        assert_none!(decode_hex("7ff"));
+        assert_none!(decode_hex("zz"));
+    }
+}
@@ -1,12 +1,14 @@
 #[cfg(feature = "functional")]
 use frunk::{Monoid, Semigroup};
 
-use crate::envelope::{Correlation, ReceivedAt};
-use crate::{generator, Id, Label, Labeling};
+use crate::envelope::{Correlation, Extension, Extensions, ReceivedAt, TraceContext, TraceParent, TraceState};
+use crate::{generator, AlphabetCodec, Id, IdPrettifier, Label, RelabelFrom, SnowflakeId};
 use iso8601_timestamp::Timestamp;
+use itertools::Itertools;
 use once_cell::sync::Lazy;
 use pretty_type_name::pretty_type_name;
-use serde::{de, Deserialize, Deserializer, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::fmt;
@@ -14,10 +16,24 @@ use std::marker::PhantomData;
 use std::ops::Deref;
 use std::str::FromStr;
 use std::string::ToString;
+use thiserror::Error;
 
 pub static CORRELATION_SNOWFLAKE_ID_KEY: Lazy<String> = Lazy::new(|| "correlation_snowflake_id".to_string());
 pub static CORRELATION_PRETTY_ID_KEY: Lazy<String> = Lazy::new(|| "correlation_pretty_id".to_string());
 pub static RECV_TIMESTAMP_KEY: Lazy<String> = Lazy::new(|| "recv_timestamp".to_string());
+pub static MESSAGE_SNOWFLAKE_ID_KEY: Lazy<String> = Lazy::new(|| "message_snowflake_id".to_string());
+pub static MESSAGE_PRETTY_ID_KEY: Lazy<String> = Lazy::new(|| "message_pretty_id".to_string());
+pub static CAUSATION_SNOWFLAKE_ID_KEY: Lazy<String> = Lazy::new(|| "causation_snowflake_id".to_string());
+pub static CAUSATION_PRETTY_ID_KEY: Lazy<String> = Lazy::new(|| "causation_pretty_id".to_string());
+pub static PARENT_SNOWFLAKE_IDS_KEY: Lazy<String> = Lazy::new(|| "parent_snowflake_ids".to_string());
+pub static TRACEPARENT_KEY: Lazy<String> = Lazy::new(|| "traceparent".to_string());
+pub static TRACESTATE_KEY: Lazy<String> = Lazy::new(|| "tracestate".to_string());
+
+// Parent ids are joined into a single value of the metadata map.
+const PARENT_IDS_DELIMITER: &str = ",";
+
+/// Prefixes the keys of extensions in the metadata map, where they are carried as JSON.
+pub const EXTENSION_KEY_PREFIX: &str = "ext.";
 
 pub trait IntoMetaData {
     type CorrelatedType: Label;
@@ -29,21 +45,51 @@ impl IntoMetaData for HashMap<String, String> {
     type CorrelatedType = ();
 
     fn into_metadata(mut self) -> MetaData<Self::CorrelatedType> {
-        let _dropped = self.remove(CORRELATION_PRETTY_ID_KEY.deref());
-
-        let correlation_id =
-            self.remove(CORRELATION_SNOWFLAKE_ID_KEY.deref())
-                .map_or_else(generator::next_id, |snowflake_rep| {
-                    i64::from_str(snowflake_rep.as_str())
-                        .map(|id| {
-                            Id::new(
-                                <Self::CorrelatedType as Label>::labeler().label(),
-                                id,
-                                &generator::prettifier(),
-                            )
-                        })
-                        .unwrap_or_else(|_| generator::next_id())
-                });
+        for pretty_key in [
+            &CORRELATION_PRETTY_ID_KEY,
+            &MESSAGE_PRETTY_ID_KEY,
+            &CAUSATION_PRETTY_ID_KEY,
+        ] {
+            let _dropped = self.remove(pretty_key.deref());
+        }
+
+        let prettifier = generator::prettifier();
+        let parse_id = |snowflake_rep: &str| {
+            i64::from_str(snowflake_rep)
+                .ok()
+                .map(|id| Id::from_snowflake(id, &prettifier))
+        };
+
+        let correlation_id = self
+            .remove(CORRELATION_SNOWFLAKE_ID_KEY.deref())
+            .and_then(|rep| parse_id(&rep))
+            .unwrap_or_else(generator::next_id);
+
+        let message_id = self
+            .remove(MESSAGE_SNOWFLAKE_ID_KEY.deref())
+            .and_then(|rep| parse_id(&rep));
+
+        let causation_id = self
+            .remove(CAUSATION_SNOWFLAKE_ID_KEY.deref())
+            .and_then(|rep| parse_id(&rep));
+
+        let parent_ids = self
+            .remove(PARENT_SNOWFLAKE_IDS_KEY.deref())
+            .map(|reps| reps.split(PARENT_IDS_DELIMITER).filter_map(parse_id).collect())
+            .unwrap_or_default();
+
+        // An invalid traceparent is ignored, as the W3C specification requires, while an invalid
+        // tracestate is discarded without the traceparent it accompanies.
+        let tracestate = self.remove(TRACESTATE_KEY.deref());
+        let trace_context = self
+            .remove(TRACEPARENT_KEY.deref())
+            .and_then(|traceparent| TraceParent::from_str(&traceparent).ok())
+            .map(|traceparent| {
+                let tracestate = tracestate
+                    .and_then(|ts| TraceState::from_str(&ts).ok())
+                    .unwrap_or_default();
+                TraceContext::new(traceparent, tracestate)
+            });
 
         let recv_timestamp = self
             .remove(RECV_TIMESTAMP_KEY.deref())
@@ -51,30 +97,268 @@ impl IntoMetaData for HashMap<String, String> {
                 Timestamp::parse(ts.as_str()).unwrap_or_else(Timestamp::now_utc)
             });
 
+        // Entries that are not JSON, such as `ext.source=web` set by a service unaware of
+        // extensions, are kept as custom entries.
+        let mut extensions = Extensions::new();
+        for (key, rep) in take_extension_entries(&mut self) {
+            match serde_json::from_str(&rep) {
+                Ok(value) => {
+                    extensions.insert_value(key, value);
+                },
+                Err(_) => {
+                    self.insert(format!("{EXTENSION_KEY_PREFIX}{key}"), rep);
+                },
+            }
+        }
+
+        let custom = if !self.is_empty() { Some(self) } else { None };
+
+        let metadata = MetaData::from_parts(correlation_id, recv_timestamp, custom);
+        let metadata = match message_id {
+            Some(message_id) => metadata.with_message_id(message_id),
+            None => metadata,
+        };
+        metadata
+            .with_lineage(causation_id, parent_ids)
+            .with_trace_context(trace_context)
+            .with_extensions(extensions)
+    }
+}
+
+/// A metadata field that fails strict conversion via [`TryIntoMetaData`].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MetaDataError {
+    #[error("Missing metadata field: {0}")]
+    MissingField(String),
+
+    #[error("Malformed metadata field {key}: {value:?}")]
+    MalformedField { key: String, value: String },
+
+    #[error("Metadata field {key} ({pretty:?}) is inconsistent with snowflake id {snowflake}")]
+    InconsistentPrettyId {
+        key: String,
+        pretty: String,
+        snowflake: i64,
+    },
+}
+
+/// How strictly [`TryIntoMetaData`] checks the fields it converts.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Strictness {
+    /// Requires the correlation id and receipt timestamp, and rejects malformed fields and pretty
+    /// ids inconsistent with their snowflake ids.
+    Strict,
+
+    /// Converts as [`IntoMetaData`] does: a missing or malformed correlation id is replaced by a new
+    /// one, a missing or malformed receipt timestamp by the current time, pretty ids are ignored and
+    /// malformed lineage ids and trace contexts are dropped.
+    Lenient,
+}
+
+pub trait TryIntoMetaData {
+    type CorrelatedType: Label;
+    type Error;
+
+    fn try_into_metadata(self, strictness: Strictness) -> Result<MetaData<Self::CorrelatedType>, Self::Error>;
+}
+
+impl TryIntoMetaData for HashMap<String, String> {
+    type CorrelatedType = ();
+    type Error = MetaDataError;
+
+    fn try_into_metadata(mut self, strictness: Strictness) -> Result<MetaData<Self::CorrelatedType>, Self::Error> {
+        if strictness == Strictness::Lenient {
+            return Ok(self.into_metadata());
+        }
+
+        let prettifier = generator::prettifier();
+        let correlation_id = take_checked_id(
+            &mut self,
+            &CORRELATION_SNOWFLAKE_ID_KEY,
+            &CORRELATION_PRETTY_ID_KEY,
+            &prettifier,
+        )?
+        .ok_or_else(|| MetaDataError::MissingField(CORRELATION_SNOWFLAKE_ID_KEY.clone()))?;
+        let message_id = take_checked_id(
+            &mut self,
+            &MESSAGE_SNOWFLAKE_ID_KEY,
+            &MESSAGE_PRETTY_ID_KEY,
+            &prettifier,
+        )?;
+        let causation_id = take_checked_id(
+            &mut self,
+            &CAUSATION_SNOWFLAKE_ID_KEY,
+            &CAUSATION_PRETTY_ID_KEY,
+            &prettifier,
+        )?;
+
+        let parent_ids = match self.remove(PARENT_SNOWFLAKE_IDS_KEY.deref()) {
+            None => Vec::new(),
+            Some(reps) => reps
+                .split(PARENT_IDS_DELIMITER)
+                .map(|rep| {
+                    i64::from_str(rep)
+                        .map(|id| Id::from_snowflake(id, &prettifier))
+                        .map_err(|_| malformed(&PARENT_SNOWFLAKE_IDS_KEY, &reps))
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        let tracestate = self.remove(TRACESTATE_KEY.deref());
+        let trace_context = match (self.remove(TRACEPARENT_KEY.deref()), tracestate) {
+            (None, None) => None,
+            (None, Some(_)) => return Err(MetaDataError::MissingField(TRACEPARENT_KEY.clone())),
+            (Some(traceparent), tracestate) => {
+                let traceparent =
+                    TraceParent::from_str(&traceparent).map_err(|_| malformed(&TRACEPARENT_KEY, &traceparent))?;
+                let tracestate = match tracestate {
+                    None => TraceState::default(),
+                    Some(ts) => TraceState::from_str(&ts).map_err(|_| malformed(&TRACESTATE_KEY, &ts))?,
+                };
+                Some(TraceContext::new(traceparent, tracestate))
+            },
+        };
+
+        let recv_timestamp = self
+            .remove(RECV_TIMESTAMP_KEY.deref())
+            .ok_or_else(|| MetaDataError::MissingField(RECV_TIMESTAMP_KEY.clone()))?;
+        let recv_timestamp =
+            Timestamp::parse(recv_timestamp.as_str()).ok_or_else(|| malformed(&RECV_TIMESTAMP_KEY, &recv_timestamp))?;
+
+        let mut extensions = Extensions::new();
+        for (key, rep) in take_extension_entries(&mut self) {
+            let value =
+                serde_json::from_str(&rep).map_err(|_| malformed(&format!("{EXTENSION_KEY_PREFIX}{key}"), &rep))?;
+            extensions.insert_value(key, value);
+        }
+
         let custom = if !self.is_empty() { Some(self) } else { None };
 
-        MetaData::from_parts(correlation_id, recv_timestamp, custom)
+        let metadata = MetaData::from_parts(correlation_id, recv_timestamp, custom);
+        let metadata = match message_id {
+            Some(message_id) => metadata.with_message_id(message_id),
+            None => metadata,
+        };
+        Ok(metadata
+            .with_lineage(causation_id, parent_ids)
+            .with_trace_context(trace_context)
+            .with_extensions(extensions))
+    }
+}
+
+/// Removes the extension entries from the metadata map, returning them by extension key.
+fn take_extension_entries(map: &mut HashMap<String, String>) -> Vec<(String, String)> {
+    let (entries, rest): (HashMap<_, _>, HashMap<_, _>) = std::mem::take(map)
+        .into_iter()
+        .partition(|(key, _)| key.starts_with(EXTENSION_KEY_PREFIX));
+    *map = rest;
+    entries
+        .into_iter()
+        .map(|(key, rep)| (key[EXTENSION_KEY_PREFIX.len()..].to_string(), rep))
+        .collect()
+}
+
+fn malformed(key: &str, value: &str) -> MetaDataError {
+    MetaDataError::MalformedField { key: key.to_string(), value: value.to_string() }
+}
+
+/// Removes an id from the metadata map, checking its pretty form, if present, against its
+/// snowflake.
+fn take_checked_id(
+    map: &mut HashMap<String, String>, snowflake_key: &str, pretty_key: &str, prettifier: &IdPrettifier<AlphabetCodec>,
+) -> Result<Option<Id<()>>, MetaDataError> {
+    let pretty = map.remove(pretty_key);
+    let snowflake = match (map.remove(snowflake_key), pretty.as_ref()) {
+        (None, None) => return Ok(None),
+        (None, Some(_)) => return Err(MetaDataError::MissingField(snowflake_key.to_string())),
+        (Some(rep), _) => i64::from_str(&rep).map_err(|_| malformed(snowflake_key, &rep))?,
+    };
+
+    if let Some(pretty) = pretty {
+        let seed = prettifier
+            .to_id_seed(&pretty)
+            .map_err(|_| malformed(pretty_key, &pretty))?;
+        if seed != SnowflakeId::from(snowflake) {
+            return Err(MetaDataError::InconsistentPrettyId { key: pretty_key.to_string(), pretty, snowflake });
+        }
     }
+
+    Ok(Some(Id::from_snowflake(snowflake, prettifier)))
+}
+
+/// How [`MetaData::merge`] combines two metadata, such as when aggregating envelopes.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MergePolicy {
+    /// Keeps the metadata received earliest, discarding the other.
+    KeepEarliest,
+
+    /// Keeps the metadata received latest, discarding the other. On equal receipt timestamps, the
+    /// left metadata is kept. This is the policy of `Add` and, with the `functional` feature,
+    /// `Semigroup`.
+    KeepLatest,
+
+    /// Keeps the left metadata, adding the custom entries and extensions of the right metadata
+    /// under keys missing from the left.
+    UnionLeftBias,
+
+    /// Keeps the right metadata, adding the custom entries and extensions of the left metadata
+    /// under keys missing from the right.
+    UnionRightBias,
+
+    /// Keeps the metadata received latest, with the custom entries and extensions of both, failing
+    /// if they hold different values under the same key.
+    FailOnConflict,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MergeError {
+    #[error("Conflicting values of custom metadata entry {key}: {left:?} and {right:?}")]
+    ConflictingCustomEntry { key: String, left: String, right: String },
+
+    #[error("Conflicting values of metadata extension {key}: {left} and {right}")]
+    ConflictingExtension { key: String, left: String, right: String },
 }
 
 /// A set of metdata regarding the envelope contents.
-#[derive(Serialize)]
+///
+/// Besides the correlation id, which identifies the message flow, metadata carries the lineage of
+/// its message: the id of the message itself, the id of the message that caused it, if any, and
+/// the chain of parent message ids from the root of the flow up to the causing message. The
+/// message id of a message starting a flow is its correlation id.
 pub struct MetaData<T> {
     correlation_id: Id<T>,
     recv_timestamp: Timestamp,
     custom: HashMap<String, String>,
+    message_id: Id<T>,
+    causation_id: Option<Id<T>>,
+    parent_ids: Vec<Id<T>>,
+    trace_context: Option<TraceContext>,
+    extensions: Extensions,
 }
 
 impl<T> fmt::Debug for MetaData<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut debug = f.debug_struct("MetaData");
         debug.field("correlation", &self.correlation_id);
+        if self.message_id != self.correlation_id {
+            debug.field("message", &self.message_id);
+        }
+        if let Some(causation_id) = &self.causation_id {
+            debug.field("causation", causation_id);
+        }
+        if let Some(trace_context) = &self.trace_context {
+            debug.field("traceparent", &trace_context.traceparent().to_string());
+        }
         debug.field("recv_timestamp", &self.recv_timestamp.to_string());
 
         if !self.custom.is_empty() {
             debug.field("custom", &self.custom);
         }
 
+        if !self.extensions.is_empty() {
+            debug.field("extensions", &self.extensions);
+        }
+
         debug.finish()
     }
 }
@@ -96,14 +380,117 @@ where
 }
 
 impl<T> MetaData<T> {
+    /// Creates metadata for a message starting a flow, so the message id is the correlation id.
     pub fn from_parts(
         correlation_id: Id<T>, recv_timestamp: Timestamp, custom: Option<HashMap<String, String>>,
     ) -> Self {
         Self {
+            message_id: correlation_id.clone(),
             correlation_id,
             recv_timestamp,
             custom: custom.unwrap_or_default(),
+            causation_id: None,
+            parent_ids: Vec::new(),
+            trace_context: None,
+            extensions: Extensions::new(),
+        }
+    }
+
+    fn from_lineage_parts(
+        correlation_id: Id<T>, recv_timestamp: Timestamp, custom: HashMap<String, String>, message_id: Option<Id<T>>,
+        causation_id: Option<Id<T>>, parent_ids: Vec<Id<T>>, trace_context: Option<TraceContext>,
+    ) -> Self {
+        Self {
+            message_id: message_id.unwrap_or_else(|| correlation_id.clone()),
+            correlation_id,
+            recv_timestamp,
+            custom,
+            causation_id,
+            parent_ids,
+            trace_context,
+            extensions: Extensions::new(),
+        }
+    }
+
+    pub const fn message_id(&self) -> &Id<T> {
+        &self.message_id
+    }
+
+    /// The id of the message that caused this one, if any.
+    pub const fn causation_id(&self) -> Option<&Id<T>> {
+        self.causation_id.as_ref()
+    }
+
+    /// The ids of the messages leading to this one, from the root of the flow up to and including
+    /// the causing message.
+    pub const fn parent_ids(&self) -> &[Id<T>] {
+        self.parent_ids.as_slice()
+    }
+
+    /// The W3C trace context the message was sent with, if any.
+    pub const fn trace_context(&self) -> Option<&TraceContext> {
+        self.trace_context.as_ref()
+    }
+
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn with_trace_context(self, trace_context: Option<TraceContext>) -> Self {
+        Self { trace_context, ..self }
+    }
+
+    /// The typed extensions carried with the message, such as a tenant id.
+    pub const fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub const fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn with_extensions(self, extensions: Extensions) -> Self {
+        Self { extensions, ..self }
+    }
+
+    /// The extension of type `E`, if any; see [`Extensions::get`].
+    pub fn get<E: Extension>(&self) -> Option<&E> {
+        self.extensions.get()
+    }
+
+    /// Inserts an extension, returning the previous extension of its type, if any.
+    pub fn insert<E: Extension>(&mut self, extension: E) -> Option<E> {
+        self.extensions.insert(extension)
+    }
+
+    /// Opens a span for handling the message, recording its correlation and message ids along with
+    /// the trace and parent span ids of its trace context, if any.
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> tracing::Span {
+        let span = tracing::info_span!(
+            "envelope",
+            correlation_id = %self.correlation_id,
+            message_id = %self.message_id,
+            trace_id = tracing::field::Empty,
+            parent_span_id = tracing::field::Empty,
+        );
+
+        if let Some(traceparent) = self.trace_context.as_ref().map(TraceContext::traceparent) {
+            span.record("trace_id", format!("{:032x}", traceparent.trace_id()));
+            span.record("parent_span_id", format!("{:016x}", traceparent.parent_id()));
         }
+
+        span
+    }
+
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn with_message_id(self, message_id: Id<T>) -> Self {
+        Self { message_id, ..self }
+    }
+
+    /// Sets the causation id and parent chain, such as when reconstructing metadata transported
+    /// elsewhere. Metadata for a new message should be created via [`MetaData::caused_by`].
+    #[allow(clippy::missing_const_for_fn)]
+    pub fn with_lineage(self, causation_id: Option<Id<T>>, parent_ids: Vec<Id<T>>) -> Self {
+        Self { causation_id, parent_ids, ..self }
     }
 
     #[allow(clippy::missing_const_for_fn)]
@@ -116,11 +503,109 @@ impl<T> MetaData<T> {
         (self.correlation_id, self.recv_timestamp, self.custom)
     }
 
-    pub fn relabel<U: Label>(self) -> MetaData<U> {
+    /// Converts the metadata into metadata for `U`, which must declare the conversion via
+    /// [`RelabelFrom`].
+    pub fn relabel<U: Label + RelabelFrom<T>>(self) -> MetaData<U> {
+        self.unchecked_relabel()
+    }
+
+    /// Converts the metadata into metadata for any labeled type; see [`Id::unchecked_relabel`].
+    pub fn unchecked_relabel<U: Label>(self) -> MetaData<U> {
         MetaData {
-            correlation_id: self.correlation_id.relabel(),
+            correlation_id: self.correlation_id.unchecked_relabel(),
             recv_timestamp: self.recv_timestamp,
             custom: self.custom,
+            message_id: self.message_id.unchecked_relabel(),
+            causation_id: self.causation_id.as_ref().map(Id::unchecked_relabel),
+            parent_ids: self.parent_ids.iter().map(Id::unchecked_relabel).collect(),
+            trace_context: self.trace_context,
+            extensions: self.extensions,
+        }
+    }
+}
+
+impl<T> MetaData<T> {
+    /// Merges two metadata according to `policy`. Only [`MergePolicy::FailOnConflict`] may fail.
+    pub fn merge(self, other: Self, policy: MergePolicy) -> Result<Self, MergeError> {
+        match policy {
+            MergePolicy::KeepEarliest => Ok(if other < self { other } else { self }),
+            MergePolicy::KeepLatest => Ok(self.keep_latest(other)),
+            MergePolicy::UnionLeftBias => Ok(self.union(other)),
+            MergePolicy::UnionRightBias => Ok(other.union(self)),
+            MergePolicy::FailOnConflict => {
+                self.check_conflicts(&other)?;
+                Ok(if self < other { other.union(self) } else { self.union(other) })
+            },
+        }
+    }
+
+    fn keep_latest(self, other: Self) -> Self {
+        if self < other {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn union(mut self, other: Self) -> Self {
+        for (key, value) in other.custom {
+            self.custom.entry(key).or_insert(value);
+        }
+        self.extensions.union(other.extensions);
+        self
+    }
+
+    fn check_conflicts(&self, other: &Self) -> Result<(), MergeError> {
+        for (key, left) in &self.custom {
+            match other.custom.get(key) {
+                Some(right) if right != left => {
+                    return Err(MergeError::ConflictingCustomEntry {
+                        key: key.clone(),
+                        left: left.clone(),
+                        right: right.clone(),
+                    });
+                },
+                _ => {},
+            }
+        }
+
+        for key in self.extensions.keys() {
+            let left = self.extensions.get_value(key);
+            let right = other.extensions.get_value(key);
+            if let (Some(left), Some(right)) = (left, right) {
+                if left != right {
+                    return Err(MergeError::ConflictingExtension {
+                        key: key.to_string(),
+                        left: left.to_string(),
+                        right: right.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: Label> MetaData<T> {
+    /// Creates metadata for a new message caused by the message of `cause`. The new message joins
+    /// the flow of its cause, so it takes the correlation id of `cause`, and records the message id
+    /// of `cause` as its causation id, at the end of its parent chain.
+    pub fn caused_by<U>(cause: &MetaData<U>) -> Self {
+        let causation_id: Id<T> = cause.message_id.unchecked_relabel();
+        let mut parent_ids = Vec::with_capacity(cause.parent_ids.len() + 1);
+        parent_ids.extend(cause.parent_ids.iter().map(Id::unchecked_relabel));
+        parent_ids.push(causation_id.clone());
+
+        Self {
+            correlation_id: cause.correlation_id.unchecked_relabel(),
+            recv_timestamp: Timestamp::now_utc(),
+            custom: HashMap::new(),
+            message_id: generator::next_id(),
+            causation_id: Some(causation_id),
+            parent_ids,
+            trace_context: cause.trace_context.clone(),
+            extensions: Extensions::new(),
         }
     }
 }
@@ -148,6 +633,11 @@ impl<T> Clone for MetaData<T> {
             correlation_id: self.correlation_id.clone(),
             recv_timestamp: self.recv_timestamp,
             custom: self.custom.clone(),
+            message_id: self.message_id.clone(),
+            causation_id: self.causation_id.clone(),
+            parent_ids: self.parent_ids.clone(),
+            trace_context: self.trace_context.clone(),
+            extensions: self.extensions.clone(),
         }
     }
 }
@@ -162,7 +652,7 @@ impl<T> Eq for MetaData<T> {}
 
 impl<T> PartialOrd for MetaData<T> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.recv_timestamp.partial_cmp(&other.recv_timestamp)
+        Some(self.cmp(other))
     }
 }
 
@@ -178,15 +668,13 @@ impl<T> std::hash::Hash for MetaData<T> {
     }
 }
 
+/// Keeps the metadata received latest, discarding the other along with its custom entries; i.e.,
+/// merges via [`MergePolicy::KeepLatest`]. Use [`MetaData::merge`] for another policy.
 impl<T> std::ops::Add for MetaData<T> {
     type Output = Self;
 
     fn add(self, rhs: Self) -> Self::Output {
-        if self < rhs {
-            rhs
-        } else {
-            self
-        }
+        self.keep_latest(rhs)
     }
 }
 
@@ -200,20 +688,17 @@ where
     }
 }
 
+/// Combines as `Add` does, via [`MergePolicy::KeepLatest`].
 #[cfg(feature = "functional")]
 impl<T> Semigroup for MetaData<T> {
     fn combine(&self, other: &Self) -> Self {
-        if self < other {
-            other.clone()
-        } else {
-            self.clone()
-        }
+        self.clone().keep_latest(other.clone())
     }
 }
 
 impl<T> From<MetaData<T>> for HashMap<String, String> {
     fn from(meta: MetaData<T>) -> Self {
-        let mut core = Self::with_capacity(3);
+        let mut core = Self::with_capacity(8);
         core.insert(
             CORRELATION_SNOWFLAKE_ID_KEY.clone(),
             meta.correlation_id.num().to_string(),
@@ -223,6 +708,32 @@ impl<T> From<MetaData<T>> for HashMap<String, String> {
             meta.correlation_id.pretty().to_string(),
         );
         core.insert(RECV_TIMESTAMP_KEY.clone(), meta.recv_timestamp.to_string());
+        core.insert(MESSAGE_SNOWFLAKE_ID_KEY.clone(), meta.message_id.num().to_string());
+        core.insert(MESSAGE_PRETTY_ID_KEY.clone(), meta.message_id.pretty().to_string());
+
+        if let Some(causation_id) = &meta.causation_id {
+            core.insert(CAUSATION_SNOWFLAKE_ID_KEY.clone(), causation_id.num().to_string());
+            core.insert(CAUSATION_PRETTY_ID_KEY.clone(), causation_id.pretty().to_string());
+        }
+
+        if !meta.parent_ids.is_empty() {
+            let parent_reps = meta.parent_ids.iter().map(Id::num).join(PARENT_IDS_DELIMITER);
+            core.insert(PARENT_SNOWFLAKE_IDS_KEY.clone(), parent_reps);
+        }
+
+        if let Some(trace_context) = &meta.trace_context {
+            core.insert(TRACEPARENT_KEY.clone(), trace_context.traceparent().to_string());
+            if !trace_context.tracestate().is_empty() {
+                core.insert(TRACESTATE_KEY.clone(), trace_context.tracestate().to_string());
+            }
+        }
+
+        // Extensions that cannot be represented as JSON are left out of the map.
+        for key in meta.extensions.keys() {
+            if let Some(value) = meta.extensions.get_value(key) {
+                core.insert(format!("{EXTENSION_KEY_PREFIX}{key}"), value.to_string());
+            }
+        }
 
         let mut result = meta.custom;
         result.extend(core);
@@ -234,7 +745,91 @@ impl<T> From<MetaData<T>> for HashMap<String, String> {
 const META_CORRELATION_ID: &str = "correlation_id";
 const META_RECV_TIMESTAMP: &str = "recv_timestamp";
 const META_CUSTOM: &str = "custom";
-const FIELDS: [&str; 3] = [META_CORRELATION_ID, META_RECV_TIMESTAMP, META_CUSTOM];
+const META_MESSAGE_ID: &str = "message_id";
+const META_CAUSATION_ID: &str = "causation_id";
+const META_PARENT_IDS: &str = "parent_ids";
+const META_TRACE_CONTEXT: &str = "trace_context";
+const META_EXTENSIONS: &str = "extensions";
+const FIELDS: [&str; 8] = [
+    META_CORRELATION_ID,
+    META_RECV_TIMESTAMP,
+    META_CUSTOM,
+    META_MESSAGE_ID,
+    META_CAUSATION_ID,
+    META_PARENT_IDS,
+    META_TRACE_CONTEXT,
+    META_EXTENSIONS,
+];
+
+// In human-readable formats, fields added after `correlation_id`, `recv_timestamp` and `custom`
+// are skipped while they hold their defaults, so that metadata without lineage, trace context or
+// extensions serializes as before, and remains readable by earlier versions. Compact formats, such
+// as bincode, may not be self-describing, so there all fields are written in the order and with
+// the types `visit_seq` reads them.
+impl<T> Serialize for MetaData<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let message_id = (self.message_id != self.correlation_id).then_some(&self.message_id);
+
+        if !serializer.is_human_readable() {
+            let mut s = serializer.serialize_struct("MetaData", FIELDS.len())?;
+            s.serialize_field(META_CORRELATION_ID, &self.correlation_id)?;
+            s.serialize_field(META_RECV_TIMESTAMP, &self.recv_timestamp)?;
+            s.serialize_field(META_CUSTOM, &self.custom)?;
+            s.serialize_field(META_MESSAGE_ID, &message_id)?;
+            s.serialize_field(META_CAUSATION_ID, &self.causation_id)?;
+            s.serialize_field(META_PARENT_IDS, &self.parent_ids)?;
+            s.serialize_field(META_TRACE_CONTEXT, &self.trace_context)?;
+            s.serialize_field(META_EXTENSIONS, &self.extensions)?;
+            return s.end();
+        }
+
+        let len = 3
+            + usize::from(message_id.is_some())
+            + usize::from(self.causation_id.is_some())
+            + usize::from(!self.parent_ids.is_empty())
+            + usize::from(self.trace_context.is_some())
+            + usize::from(!self.extensions.is_empty());
+
+        let mut s = serializer.serialize_struct("MetaData", len)?;
+        s.serialize_field(META_CORRELATION_ID, &self.correlation_id)?;
+        s.serialize_field(META_RECV_TIMESTAMP, &self.recv_timestamp)?;
+        s.serialize_field(META_CUSTOM, &self.custom)?;
+
+        match message_id {
+            Some(message_id) => s.serialize_field(META_MESSAGE_ID, message_id)?,
+            None => s.skip_field(META_MESSAGE_ID)?,
+        }
+
+        if self.causation_id.is_some() {
+            s.serialize_field(META_CAUSATION_ID, &self.causation_id)?;
+        } else {
+            s.skip_field(META_CAUSATION_ID)?;
+        }
+
+        if self.parent_ids.is_empty() {
+            s.skip_field(META_PARENT_IDS)?;
+        } else {
+            s.serialize_field(META_PARENT_IDS, &self.parent_ids)?;
+        }
+
+        if self.trace_context.is_some() {
+            s.serialize_field(META_TRACE_CONTEXT, &self.trace_context)?;
+        } else {
+            s.skip_field(META_TRACE_CONTEXT)?;
+        }
+
+        if self.extensions.is_empty() {
+            s.skip_field(META_EXTENSIONS)?;
+        } else {
+            s.serialize_field(META_EXTENSIONS, &self.extensions)?;
+        }
+
+        s.end()
+    }
+}
 
 impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
@@ -245,6 +840,11 @@ impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
             CorrelationId,
             RecvTimestamp,
             Custom,
+            MessageId,
+            CausationId,
+            ParentIds,
+            TraceContext,
+            Extensions,
         }
 
         impl<'de> Deserialize<'de> for Field {
@@ -258,7 +858,10 @@ impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
                     type Value = Field;
 
                     fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-                        f.write_str("`correlation_id`, `recv_timestamp` or `custom`")
+                        f.write_str(
+                            "`correlation_id`, `recv_timestamp`, `custom`, `message_id`, `causation_id`, \
+                             `parent_ids`, `trace_context` or `extensions`",
+                        )
                     }
 
                     fn visit_str<E>(self, value: &str) -> Result<Field, E>
@@ -269,6 +872,11 @@ impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
                             META_CORRELATION_ID => Ok(Self::Value::CorrelationId),
                             META_RECV_TIMESTAMP => Ok(Self::Value::RecvTimestamp),
                             META_CUSTOM => Ok(Self::Value::Custom),
+                            META_MESSAGE_ID => Ok(Self::Value::MessageId),
+                            META_CAUSATION_ID => Ok(Self::Value::CausationId),
+                            META_PARENT_IDS => Ok(Self::Value::ParentIds),
+                            META_TRACE_CONTEXT => Ok(Self::Value::TraceContext),
+                            META_EXTENSIONS => Ok(Self::Value::Extensions),
                             _ => Err(de::Error::unknown_field(value, &FIELDS)),
                         }
                     }
@@ -279,12 +887,24 @@ impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
         }
 
         struct MetaVisitor<T: Label> {
+            human_readable: bool,
             marker: PhantomData<T>,
         }
 
         impl<T: Label> MetaVisitor<T> {
-            pub const fn new() -> Self {
-                Self { marker: PhantomData }
+            pub const fn new(human_readable: bool) -> Self {
+                Self { human_readable, marker: PhantomData }
+            }
+
+            // `Timestamp` requires `deserialize_any`, which formats that are not self-describing do
+            // not support, so in formats that are not human-readable, the milliseconds since the
+            // UNIX epoch that `Timestamp` serializes there are read directly.
+            fn next_timestamp<'de, V: de::SeqAccess<'de>>(&self, seq: &mut V) -> Result<Option<Timestamp>, V::Error> {
+                if self.human_readable {
+                    seq.next_element()
+                } else {
+                    seq.next_element::<EpochMillis>().map(|millis| millis.map(|m| m.0))
+                }
             }
         }
 
@@ -300,11 +920,28 @@ impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
                 V: de::SeqAccess<'de>,
             {
                 let correlation_id: Id<T> = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
-                let recv_timestamp: Timestamp =
-                    seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let recv_timestamp: Timestamp = self
+                    .next_timestamp(&mut seq)?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
                 let custom: HashMap<String, String> =
                     seq.next_element()?.ok_or_else(|| de::Error::invalid_length(2, &self))?;
-                Ok(MetaData::from_parts(correlation_id, recv_timestamp, Some(custom)))
+
+                // Lineage was added after the other fields, so it is optional for compatibility.
+                let message_id: Option<Id<T>> = seq.next_element()?.flatten();
+                let causation_id: Option<Id<T>> = seq.next_element()?.flatten();
+                let parent_ids: Vec<Id<T>> = seq.next_element()?.unwrap_or_default();
+                let trace_context: Option<TraceContext> = seq.next_element()?.flatten();
+                let extensions: Extensions = seq.next_element()?.unwrap_or_default();
+                Ok(MetaData::from_lineage_parts(
+                    correlation_id,
+                    recv_timestamp,
+                    custom,
+                    message_id,
+                    causation_id,
+                    parent_ids,
+                    trace_context,
+                )
+                .with_extensions(extensions))
             }
 
             fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
@@ -314,6 +951,11 @@ impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
                 let mut correlation_id = None;
                 let mut recv_timestamp = None;
                 let mut custom = None;
+                let mut message_id = None;
+                let mut causation_id = None;
+                let mut parent_ids = None;
+                let mut trace_context = None;
+                let mut extensions = None;
 
                 while let Some(key) = map.next_key()? {
                     match key {
@@ -328,7 +970,11 @@ impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
                             if recv_timestamp.is_some() {
                                 return Err(de::Error::duplicate_field(META_RECV_TIMESTAMP));
                             }
-                            recv_timestamp = Some(map.next_value()?);
+                            recv_timestamp = Some(if self.human_readable {
+                                map.next_value()?
+                            } else {
+                                map.next_value::<EpochMillis>()?.0
+                            });
                         },
 
                         Field::Custom => {
@@ -337,6 +983,41 @@ impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
                             }
                             custom = Some(map.next_value()?);
                         },
+
+                        Field::MessageId => {
+                            if message_id.is_some() {
+                                return Err(de::Error::duplicate_field(META_MESSAGE_ID));
+                            }
+                            message_id = Some(map.next_value()?);
+                        },
+
+                        Field::CausationId => {
+                            if causation_id.is_some() {
+                                return Err(de::Error::duplicate_field(META_CAUSATION_ID));
+                            }
+                            causation_id = Some(map.next_value()?);
+                        },
+
+                        Field::ParentIds => {
+                            if parent_ids.is_some() {
+                                return Err(de::Error::duplicate_field(META_PARENT_IDS));
+                            }
+                            parent_ids = Some(map.next_value()?);
+                        },
+
+                        Field::TraceContext => {
+                            if trace_context.is_some() {
+                                return Err(de::Error::duplicate_field(META_TRACE_CONTEXT));
+                            }
+                            trace_context = Some(map.next_value()?);
+                        },
+
+                        Field::Extensions => {
+                            if extensions.is_some() {
+                                return Err(de::Error::duplicate_field(META_EXTENSIONS));
+                            }
+                            extensions = Some(map.next_value()?);
+                        },
                     }
                 }
 
@@ -345,11 +1026,37 @@ impl<'de, T: Label> Deserialize<'de> for MetaData<T> {
                 let recv_timestamp: Timestamp =
                     recv_timestamp.ok_or_else(|| de::Error::missing_field(META_RECV_TIMESTAMP))?;
                 let custom: HashMap<String, String> = custom.ok_or_else(|| de::Error::missing_field(META_CUSTOM))?;
-                Ok(MetaData::from_parts(correlation_id, recv_timestamp, Some(custom)))
+                Ok(MetaData::from_lineage_parts(
+                    correlation_id,
+                    recv_timestamp,
+                    custom,
+                    message_id.flatten(),
+                    causation_id.flatten(),
+                    parent_ids.unwrap_or_default(),
+                    trace_context.flatten(),
+                )
+                .with_extensions(extensions.unwrap_or_default()))
             }
         }
 
-        deserializer.deserialize_struct("MetaData", &FIELDS, MetaVisitor::<T>::new())
+        let human_readable = deserializer.is_human_readable();
+        deserializer.deserialize_struct("MetaData", &FIELDS, MetaVisitor::<T>::new(human_readable))
+    }
+}
+
+/// A timestamp read as milliseconds since the UNIX epoch.
+struct EpochMillis(Timestamp);
+
+impl<'de> Deserialize<'de> for EpochMillis {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let millis = i64::deserialize(deserializer)?;
+        Timestamp::UNIX_EPOCH
+            .checked_add(iso8601_timestamp::Duration::milliseconds(millis))
+            .map(Self)
+            .ok_or_else(|| de::Error::custom(format!("Timestamp out of range: {millis} ms")))
     }
 }
 
@@ -358,6 +1065,7 @@ mod tests {
     use super::*;
     use crate::envelope::Envelope;
     use crate::{Label, Labeling, MakeLabeling};
+    use claim::assert_some;
     use pretty_assertions::assert_eq;
     use serde_test::Configure;
     use serde_test::{assert_tokens, Token};
@@ -448,6 +1156,97 @@ mod tests {
         assert_eq!(actual.as_ref(), &expected);
     }
 
+    impl RelabelFrom<TestData> for TestContainer {}
+    impl RelabelFrom<TestData> for TestEnvelopeContainer {}
+
+    #[tokio::test]
+    async fn test_envelope_checked_map() {
+        let enveloped_data = Envelope::new(TestData(13));
+        let correlation_id = enveloped_data.metadata().correlation().num();
+
+        let actual = enveloped_data.clone().checked_map(TestContainer);
+        assert_eq!(actual.metadata().correlation().num(), correlation_id);
+        assert_eq!(actual.metadata().correlation().label(), "TestContainer");
+        assert_eq!(actual.as_ref(), &TestContainer(TestData(13)));
+
+        let actual = enveloped_data.clone().checked_flat_map(TestEnvelopeContainer);
+        assert_eq!(actual.metadata().correlation().num(), correlation_id);
+        assert_eq!(actual.as_ref(), &TestEnvelopeContainer(enveloped_data.clone()));
+
+        let actual = enveloped_data.checked_and_then(|d| async move { TestContainer(d) }).await;
+        assert_eq!(actual.metadata().correlation().num(), correlation_id);
+        assert_eq!(actual.as_ref(), &TestContainer(TestData(13)));
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct TestFailure(String);
+
+    impl Label for TestFailure {
+        type Labeler = MakeLabeling<Self>;
+
+        fn labeler() -> Self::Labeler {
+            MakeLabeling::default()
+        }
+    }
+
+    #[test]
+    fn test_envelope_try_map() {
+        let enveloped_data = Envelope::caused_by(TestData(13), &Envelope::new(TestData(1)));
+        let metadata = enveloped_data.metadata().clone();
+
+        let actual = enveloped_data.clone().try_map(|d| Ok::<_, TestFailure>(TestContainer(d)));
+        let actual = actual.unwrap();
+        assert_eq!(actual.metadata().correlation().num(), metadata.correlation().num());
+        assert_eq!(actual.as_ref(), &TestContainer(TestData(13)));
+
+        let actual = enveloped_data.try_map(|d| Err::<TestContainer, _>(TestFailure(format!("bad {}", d.0))));
+        let failed = actual.unwrap_err();
+        assert_eq!(failed.metadata.correlation().num(), metadata.correlation().num());
+        assert_eq!(failed.metadata.message_id().num(), metadata.message_id().num());
+        assert_eq!(
+            failed.metadata.causation_id().map(Id::num),
+            metadata.causation_id().map(Id::num)
+        );
+        assert_eq!(failed.metadata.recv_timestamp(), metadata.recv_timestamp());
+        assert_eq!(failed.error, TestFailure("bad 13".to_string()));
+
+        let dead_letter = failed.into_envelope();
+        assert_eq!(dead_letter.metadata().correlation().num(), metadata.correlation().num());
+        assert_eq!(dead_letter.as_ref(), &TestFailure("bad 13".to_string()));
+    }
+
+    #[test]
+    fn test_envelope_try_map_with_unlabeled_error() {
+        let enveloped_data = Envelope::new("thirteen".to_string());
+        let correlation_id = enveloped_data.correlation().num();
+
+        let failed = enveloped_data.try_map(|s| s.parse::<i32>().map(TestData)).unwrap_err();
+        assert_eq!(failed.metadata.correlation().num(), correlation_id);
+        assert_eq!(failed.error, "thirteen".parse::<i32>().unwrap_err());
+
+        let error = std::error::Error::source(&failed).unwrap();
+        assert_eq!(error.to_string(), "invalid digit found in string");
+    }
+
+    #[tokio::test]
+    async fn test_envelope_try_and_then() {
+        let enveloped_data = Envelope::new(TestData(13));
+        let metadata = enveloped_data.metadata().clone();
+
+        let actual = enveloped_data
+            .clone()
+            .try_and_then(|d| async move { Ok::<_, std::io::Error>(TestContainer(d)) })
+            .await;
+        assert_eq!(actual.unwrap().as_ref(), &TestContainer(TestData(13)));
+
+        let actual = enveloped_data
+            .try_and_then(|d| async move { Err::<TestContainer, _>(std::io::Error::other(format!("bad {}", d.0))) })
+            .await;
+        let failed = actual.unwrap_err();
+        assert_eq!(failed.metadata.correlation().num(), metadata.correlation().num());
+        assert_eq!(failed.error.to_string(), "bad 13");
+    }
+
     #[test]
     fn test_envelope_serde_tokens() {
         let data = TestData(17);
@@ -479,4 +1278,500 @@ mod tests {
             ],
         )
     }
+
+    #[test]
+    fn test_caused_by_links_lineage() {
+        let root = Envelope::new(TestData(1));
+        assert_eq!(root.metadata().message_id(), root.metadata().correlation());
+        assert_eq!(root.metadata().causation_id(), None);
+        assert!(root.metadata().parent_ids().is_empty());
+
+        let child = Envelope::caused_by(TestContainer(TestData(2)), &root);
+        let grandchild = Envelope::caused_by(TestData(3), &child);
+
+        let child_meta = child.metadata();
+        assert_eq!(child_meta.correlation().num(), root.metadata().correlation().num());
+        assert_ne!(child_meta.message_id().num(), root.metadata().message_id().num());
+        assert_eq!(
+            child_meta.causation_id().map(Id::num),
+            Some(root.metadata().message_id().num())
+        );
+        assert_eq!(child_meta.causation_id().map(Id::label), Some("TestContainer"));
+
+        let grandchild_meta = grandchild.metadata();
+        assert_eq!(grandchild_meta.correlation().num(), root.metadata().correlation().num());
+        assert_eq!(
+            grandchild_meta.causation_id().map(Id::num),
+            Some(child_meta.message_id().num())
+        );
+        let parents: Vec<_> = grandchild_meta.parent_ids().iter().map(Id::num).collect();
+        assert_eq!(
+            parents,
+            vec![root.metadata().message_id().num(), child_meta.message_id().num()]
+        );
+    }
+
+    #[test]
+    fn test_lineage_survives_serde() {
+        let root = Envelope::new(TestData(1));
+        let child = Envelope::caused_by(TestData(2), &Envelope::caused_by(TestData(3), &root));
+        let expected = child.metadata();
+
+        let json = serde_json::to_string(expected).unwrap();
+        let actual: MetaData<TestData> = serde_json::from_str(&json).unwrap();
+        assert_eq!(actual.message_id(), expected.message_id());
+        assert_eq!(actual.causation_id(), expected.causation_id());
+        assert_eq!(actual.parent_ids(), expected.parent_ids());
+        assert_eq!(actual.correlation(), expected.correlation());
+    }
+
+    #[test]
+    fn test_lineage_survives_hashmap() {
+        let root = Envelope::new(TestData(1));
+        let child = Envelope::caused_by(TestData(2), &Envelope::caused_by(TestData(3), &root));
+        let expected = child.metadata().clone();
+
+        let map: HashMap<String, String> = expected.clone().into();
+        assert_eq!(
+            map.get(CAUSATION_PRETTY_ID_KEY.as_str()).map(String::as_str),
+            expected.causation_id().map(Id::pretty)
+        );
+
+        let actual = map.into_metadata();
+        assert_eq!(actual.message_id().num(), expected.message_id().num());
+        assert_eq!(actual.message_id().pretty(), expected.message_id().pretty());
+        assert_eq!(actual.causation_id().map(Id::num), expected.causation_id().map(Id::num));
+        let actual_parents: Vec<_> = actual.parent_ids().iter().map(Id::num).collect();
+        let expected_parents: Vec<_> = expected.parent_ids().iter().map(Id::num).collect();
+        assert_eq!(actual_parents, expected_parents);
+        assert_eq!(actual.correlation().num(), expected.correlation().num());
+    }
+
+    #[test]
+    fn test_deserialize_metadata_without_lineage() {
+        let json = format!(
+            r#"{{"correlation_id":{{"snowflake":{},"pretty":"{}"}},"recv_timestamp":"{METADATA_TS}","custom":{{}}}}"#,
+            META_DATA.correlation_id.num(),
+            META_DATA.correlation_id.pretty(),
+        );
+        let actual: MetaData<TestData> = serde_json::from_str(&json).unwrap();
+        assert_eq!(actual.message_id(), actual.correlation());
+        assert_eq!(actual.causation_id(), None);
+        assert!(actual.parent_ids().is_empty());
+
+        let legacy = legacy_map();
+        let actual = legacy.into_metadata();
+        assert_eq!(actual.message_id().num(), 824227036833910784);
+        assert_eq!(actual.causation_id(), None);
+    }
+
+    fn legacy_map() -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        map.insert(CORRELATION_SNOWFLAKE_ID_KEY.clone(), "824227036833910784".to_string());
+        map.insert(RECV_TIMESTAMP_KEY.clone(), METADATA_TS.to_string());
+        map
+    }
+
+    #[test]
+    fn test_trace_context_survives_hashmap() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let mut map = legacy_map();
+        map.insert(TRACEPARENT_KEY.clone(), traceparent.to_string());
+        map.insert(TRACESTATE_KEY.clone(), "rojo=00f067aa0ba902b7".to_string());
+
+        let metadata = map.into_metadata();
+        let context = metadata.trace_context().unwrap();
+        assert_eq!(context.traceparent().to_string(), traceparent);
+        assert_eq!(context.tracestate().get("rojo"), Some("00f067aa0ba902b7"));
+        assert!(metadata.custom.is_empty());
+
+        let child: MetaData<TestData> = MetaData::caused_by(&metadata);
+        assert_eq!(child.trace_context(), Some(context));
+
+        let map: HashMap<String, String> = child.into();
+        assert_eq!(map.get(TRACEPARENT_KEY.as_str()).map(String::as_str), Some(traceparent));
+        assert_eq!(
+            map.get(TRACESTATE_KEY.as_str()).map(String::as_str),
+            Some("rojo=00f067aa0ba902b7")
+        );
+
+        let json = serde_json::to_string(&map.into_metadata()).unwrap();
+        let actual: MetaData<()> = serde_json::from_str(&json).unwrap();
+        assert_eq!(actual.trace_context(), Some(context));
+    }
+
+    #[test]
+    fn test_invalid_trace_context_is_ignored() {
+        let mut map = legacy_map();
+        map.insert(TRACEPARENT_KEY.clone(), "00-not-a-traceparent-01".to_string());
+        map.insert(TRACESTATE_KEY.clone(), "rojo=00f067aa0ba902b7".to_string());
+
+        let metadata = map.into_metadata();
+        assert_eq!(metadata.trace_context(), None);
+        assert!(metadata.custom.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_tracestate_keeps_traceparent() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+        let mut map = legacy_map();
+        map.insert(TRACEPARENT_KEY.clone(), traceparent.to_string());
+        map.insert(TRACESTATE_KEY.clone(), "not a = tracestate".to_string());
+
+        let metadata = map.into_metadata();
+        let context = assert_some!(metadata.trace_context());
+        assert_eq!(context.traceparent().to_string(), traceparent);
+        assert!(context.tracestate().is_empty());
+        assert!(metadata.custom.is_empty());
+    }
+
+    #[test]
+    fn test_strict_metadata_from_hashmap() {
+        let root = Envelope::new(TestData(1));
+        let child = Envelope::caused_by(TestData(2), &Envelope::caused_by(TestData(3), &root));
+        let ts = Timestamp::parse(METADATA_TS).unwrap();
+        let expected = child.metadata().clone().with_recv_timestamp(ts);
+        let mut map: HashMap<String, String> = expected.clone().into();
+        map.insert("cat".to_string(), "Otis".to_string());
+
+        let actual = map.try_into_metadata(Strictness::Strict).unwrap();
+        assert_eq!(actual.correlation().num(), expected.correlation().num());
+        assert_eq!(actual.correlation().pretty(), expected.correlation().pretty());
+        assert_eq!(actual.message_id().num(), expected.message_id().num());
+        assert_eq!(actual.causation_id().map(Id::num), expected.causation_id().map(Id::num));
+        assert_eq!(actual.parent_ids().len(), 2);
+        assert_eq!(actual.recv_timestamp(), expected.recv_timestamp());
+        assert_eq!(actual.custom.get("cat").map(String::as_str), Some("Otis"));
+    }
+
+    #[test]
+    fn test_strict_metadata_rejects_missing_fields() {
+        let mut map = legacy_map();
+        map.remove(CORRELATION_SNOWFLAKE_ID_KEY.as_str());
+        assert_eq!(
+            map.try_into_metadata(Strictness::Strict).unwrap_err(),
+            MetaDataError::MissingField(CORRELATION_SNOWFLAKE_ID_KEY.clone())
+        );
+
+        let mut map = legacy_map();
+        map.remove(RECV_TIMESTAMP_KEY.as_str());
+        assert_eq!(
+            map.try_into_metadata(Strictness::Strict).unwrap_err(),
+            MetaDataError::MissingField(RECV_TIMESTAMP_KEY.clone())
+        );
+
+        let mut map = legacy_map();
+        map.insert(CAUSATION_PRETTY_ID_KEY.clone(), "HPJD-72036-HAPK-58077".to_string());
+        assert_eq!(
+            map.try_into_metadata(Strictness::Strict).unwrap_err(),
+            MetaDataError::MissingField(CAUSATION_SNOWFLAKE_ID_KEY.clone())
+        );
+
+        let mut map = legacy_map();
+        map.insert(TRACESTATE_KEY.clone(), "rojo=00f067aa0ba902b7".to_string());
+        assert_eq!(
+            map.try_into_metadata(Strictness::Strict).unwrap_err(),
+            MetaDataError::MissingField(TRACEPARENT_KEY.clone())
+        );
+    }
+
+    #[test]
+    fn test_strict_metadata_rejects_malformed_fields() {
+        let malformed = |key: &Lazy<String>, value: &str| {
+            let mut map = legacy_map();
+            map.insert(key.to_string(), value.to_string());
+            let expected = MetaDataError::MalformedField { key: key.to_string(), value: value.to_string() };
+            assert_eq!(map.try_into_metadata(Strictness::Strict).unwrap_err(), expected);
+        };
+
+        malformed(&CORRELATION_SNOWFLAKE_ID_KEY, "not-a-number");
+        malformed(&CORRELATION_PRETTY_ID_KEY, "not-a-pretty-id");
+        malformed(&RECV_TIMESTAMP_KEY, "yesterday");
+        malformed(&MESSAGE_SNOWFLAKE_ID_KEY, "");
+        malformed(&PARENT_SNOWFLAKE_IDS_KEY, "824227036833910784,oops");
+        malformed(&TRACEPARENT_KEY, "00-not-a-traceparent-01");
+
+        let mut map = legacy_map();
+        map.insert(
+            TRACEPARENT_KEY.clone(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+        );
+        map.insert(TRACESTATE_KEY.clone(), "rojo".to_string());
+        assert_eq!(
+            map.try_into_metadata(Strictness::Strict).unwrap_err(),
+            MetaDataError::MalformedField {
+                key: TRACESTATE_KEY.clone(),
+                value: "rojo".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_strict_metadata_rejects_inconsistent_pretty_id() {
+        let prettifier = generator::prettifier();
+        let other = prettifier.prettify(824227036833910785_i64);
+        let mut map = legacy_map();
+        map.insert(CORRELATION_PRETTY_ID_KEY.clone(), other.clone());
+
+        assert_eq!(
+            map.clone().try_into_metadata(Strictness::Strict).unwrap_err(),
+            MetaDataError::InconsistentPrettyId {
+                key: CORRELATION_PRETTY_ID_KEY.clone(),
+                pretty: other,
+                snowflake: 824227036833910784,
+            }
+        );
+
+        let lenient = map.try_into_metadata(Strictness::Lenient).unwrap();
+        assert_eq!(lenient.correlation().num(), 824227036833910784);
+    }
+
+    #[test]
+    fn test_lenient_metadata_from_hashmap() {
+        let mut map = HashMap::new();
+        map.insert(CORRELATION_SNOWFLAKE_ID_KEY.clone(), "not-a-number".to_string());
+        map.insert(RECV_TIMESTAMP_KEY.clone(), "yesterday".to_string());
+        map.insert(PARENT_SNOWFLAKE_IDS_KEY.clone(), "824227036833910784,oops".to_string());
+
+        let before = Timestamp::now_utc();
+        let actual = map.try_into_metadata(Strictness::Lenient).unwrap();
+        assert!(before <= actual.recv_timestamp());
+        assert_eq!(
+            actual.parent_ids().iter().map(Id::num).collect::<Vec<_>>(),
+            vec![824227036833910784]
+        );
+        assert!(actual.custom.is_empty());
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct TenantId(String);
+
+    impl Extension for TenantId {
+        const KEY: &'static str = "tenant_id";
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    struct Priority(u8);
+
+    impl Extension for Priority {
+        const KEY: &'static str = "priority";
+    }
+
+    #[test]
+    fn test_extensions_survive_serde() {
+        let mut expected = META_DATA.clone();
+        expected.insert(TenantId("acme".to_string()));
+        expected.insert(Priority(3));
+        expected.custom.insert("cat".to_string(), "Otis".to_string());
+
+        let json = serde_json::to_string(&expected).unwrap();
+        let actual: MetaData<TestData> = serde_json::from_str(&json).unwrap();
+        assert_eq!(actual.get::<TenantId>(), Some(&TenantId("acme".to_string())));
+        assert_eq!(actual.get::<Priority>(), Some(&Priority(3)));
+        assert_eq!(actual.custom.get("cat").map(String::as_str), Some("Otis"));
+
+        let json = format!(
+            r#"{{"correlation_id":{{"snowflake":{},"pretty":"{}"}},"recv_timestamp":"{METADATA_TS}","custom":{{}}}}"#,
+            META_DATA.correlation_id.num(),
+            META_DATA.correlation_id.pretty(),
+        );
+        let legacy: MetaData<TestData> = serde_json::from_str(&json).unwrap();
+        assert!(legacy.extensions().is_empty());
+    }
+
+    #[test]
+    fn test_extensions_survive_hashmap() {
+        let mut expected = META_DATA.clone();
+        expected.insert(TenantId("acme".to_string()));
+        expected.insert(Priority(3));
+
+        let map: HashMap<String, String> = expected.into();
+        assert_eq!(map.get("ext.tenant_id").map(String::as_str), Some(r#""acme""#));
+        assert_eq!(map.get("ext.priority").map(String::as_str), Some("3"));
+
+        let actual = map.clone().try_into_metadata(Strictness::Strict).unwrap();
+        assert_eq!(actual.get::<TenantId>(), Some(&TenantId("acme".to_string())));
+        assert_eq!(actual.get::<Priority>(), Some(&Priority(3)));
+        assert!(actual.custom.is_empty());
+
+        let mut map = map;
+        map.insert("ext.priority".to_string(), "{".to_string());
+        assert_eq!(
+            map.clone().try_into_metadata(Strictness::Strict).unwrap_err(),
+            MetaDataError::MalformedField {
+                key: "ext.priority".to_string(),
+                value: "{".to_string()
+            }
+        );
+
+        let lenient = map.into_metadata();
+        assert_eq!(lenient.get::<TenantId>(), Some(&TenantId("acme".to_string())));
+        assert_eq!(lenient.get::<Priority>(), None);
+        assert_eq!(lenient.custom.get("ext.priority").map(String::as_str), Some("{"));
+    }
+
+    #[test]
+    fn test_lenient_metadata_keeps_non_json_extension_entries() {
+        let mut map = legacy_map();
+        map.insert("ext.source".to_string(), "web".to_string());
+
+        let metadata = map.into_metadata();
+        assert!(metadata.extensions().is_empty());
+        assert_eq!(metadata.custom.get("ext.source").map(String::as_str), Some("web"));
+
+        let map: HashMap<String, String> = metadata.into();
+        assert_eq!(map.get("ext.source").map(String::as_str), Some("web"));
+    }
+
+    #[test]
+    fn test_metadata_survives_bincode() {
+        let plain = META_DATA.clone();
+        let bytes = bincode::serialize(&plain).unwrap();
+        let actual: MetaData<TestData> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(actual.correlation(), plain.correlation());
+        assert_eq!(actual.message_id(), plain.message_id());
+        assert_eq!(actual.recv_timestamp(), plain.recv_timestamp());
+
+        let root = Envelope::new(TestData(1));
+        let child = Envelope::caused_by(TestData(2), &Envelope::caused_by(TestData(3), &root));
+        let context = TraceContext::parse(
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            Some("rojo=00f067aa0ba902b7"),
+        )
+        .unwrap();
+        // Timestamps are serialized at millisecond precision.
+        let mut expected = child
+            .metadata()
+            .clone()
+            .with_recv_timestamp(plain.recv_timestamp())
+            .with_trace_context(Some(context));
+        expected.custom.insert("cat".to_string(), "Otis".to_string());
+        expected.insert(TenantId("acme".to_string()));
+        expected.insert(Priority(3));
+
+        let bytes = bincode::serialize(&expected).unwrap();
+        let actual: MetaData<TestData> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(actual.correlation(), expected.correlation());
+        assert_eq!(actual.recv_timestamp(), expected.recv_timestamp());
+        assert_eq!(actual.custom, expected.custom);
+        assert_eq!(actual.message_id(), expected.message_id());
+        assert_eq!(actual.causation_id(), expected.causation_id());
+        assert_eq!(actual.parent_ids(), expected.parent_ids());
+        assert_eq!(actual.trace_context(), expected.trace_context());
+        assert_eq!(actual.get::<TenantId>(), Some(&TenantId("acme".to_string())));
+        assert_eq!(actual.get::<Priority>(), Some(&Priority(3)));
+    }
+
+    fn merge_fixtures() -> (MetaData<TestData>, MetaData<TestData>) {
+        let early_ts = Timestamp::parse("2022-11-30T03:43:18.068Z").unwrap();
+        let late_ts = Timestamp::parse("2022-11-30T03:43:19.068Z").unwrap();
+
+        let mut early_custom = HashMap::new();
+        early_custom.insert("cat".to_string(), "Otis".to_string());
+        early_custom.insert("dog".to_string(), "Stella".to_string());
+        let mut early = MetaData::from_parts(generator::next_id(), early_ts, Some(early_custom));
+        early.insert(Priority(1));
+
+        let mut late_custom = HashMap::new();
+        late_custom.insert("cat".to_string(), "Neo".to_string());
+        late_custom.insert("bird".to_string(), "Tweety".to_string());
+        let mut late = MetaData::from_parts(generator::next_id(), late_ts, Some(late_custom));
+        late.insert(TenantId("acme".to_string()));
+
+        (early, late)
+    }
+
+    #[test]
+    fn test_merge_keep_policies() {
+        let (early, late) = merge_fixtures();
+
+        let actual = early.clone().merge(late.clone(), MergePolicy::KeepEarliest).unwrap();
+        assert_eq!(actual.correlation(), early.correlation());
+        assert_eq!(actual.custom, early.custom);
+
+        let actual = early.clone().merge(late.clone(), MergePolicy::KeepLatest).unwrap();
+        assert_eq!(actual.correlation(), late.correlation());
+        assert_eq!(actual.custom, late.custom);
+        assert_eq!(actual.get::<Priority>(), None);
+
+        #[cfg(feature = "functional")]
+        {
+            let combined = early.combine(&late);
+            assert_eq!(combined.correlation(), late.correlation());
+            assert_eq!(combined.custom, late.custom);
+            assert_eq!(combined.get::<Priority>(), None);
+        }
+
+        let added = early + late.clone();
+        assert_eq!(added.correlation(), late.correlation());
+        assert_eq!(added.custom, late.custom);
+    }
+
+    #[test]
+    fn test_merge_union_policies() {
+        let (early, late) = merge_fixtures();
+        let custom = |m: &MetaData<TestData>, key: &str| m.custom.get(key).cloned();
+
+        let actual = early.clone().merge(late.clone(), MergePolicy::UnionLeftBias).unwrap();
+        assert_eq!(actual.correlation(), early.correlation());
+        assert_eq!(actual.recv_timestamp(), early.recv_timestamp());
+        assert_eq!(custom(&actual, "cat").as_deref(), Some("Otis"));
+        assert_eq!(custom(&actual, "dog").as_deref(), Some("Stella"));
+        assert_eq!(custom(&actual, "bird").as_deref(), Some("Tweety"));
+        assert_eq!(actual.get::<Priority>(), Some(&Priority(1)));
+        assert_eq!(actual.get::<TenantId>(), Some(&TenantId("acme".to_string())));
+
+        let actual = early.merge(late.clone(), MergePolicy::UnionRightBias).unwrap();
+        assert_eq!(actual.correlation(), late.correlation());
+        assert_eq!(custom(&actual, "cat").as_deref(), Some("Neo"));
+        assert_eq!(custom(&actual, "dog").as_deref(), Some("Stella"));
+        assert_eq!(actual.extensions().len(), 2);
+    }
+
+    #[test]
+    fn test_merge_fail_on_conflict() {
+        let (early, mut late) = merge_fixtures();
+
+        let actual = early.clone().merge(late.clone(), MergePolicy::FailOnConflict);
+        assert_eq!(
+            actual.unwrap_err(),
+            MergeError::ConflictingCustomEntry {
+                key: "cat".to_string(),
+                left: "Otis".to_string(),
+                right: "Neo".to_string(),
+            }
+        );
+
+        late.custom.insert("cat".to_string(), "Otis".to_string());
+        late.insert(Priority(2));
+        let actual = early.clone().merge(late.clone(), MergePolicy::FailOnConflict);
+        assert_eq!(
+            actual.unwrap_err(),
+            MergeError::ConflictingExtension {
+                key: Priority::KEY.to_string(),
+                left: "1".to_string(),
+                right: "2".to_string(),
+            }
+        );
+
+        late.insert(Priority(1));
+        let actual = early.merge(late.clone(), MergePolicy::FailOnConflict).unwrap();
+        assert_eq!(actual.correlation(), late.correlation());
+        assert_eq!(actual.custom.len(), 3);
+        assert_eq!(actual.extensions().len(), 2);
+    }
+
+    #[test]
+    fn test_envelope_merge() {
+        let (early, late) = merge_fixtures();
+        let left = Envelope::from_parts(early.clone(), TestData(1));
+        let right = Envelope::from_parts(late, TestData(2));
+
+        let actual = left
+            .merge_with(right, MergePolicy::UnionLeftBias, |l, r| TestData(l.0 + r.0))
+            .unwrap();
+        assert_eq!(actual.metadata().correlation(), early.correlation());
+        assert_eq!(actual.metadata().custom.len(), 3);
+        assert_eq!(actual.as_ref(), &TestData(3));
+    }
 }
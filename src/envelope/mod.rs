@@ -1,16 +1,42 @@
 use crate::Id;
 use iso8601_timestamp::Timestamp;
 
+mod batch;
+
+mod codec;
+
 #[allow(clippy::module_inception)]
 mod envelope;
 
+mod extensions;
+
+#[cfg(feature = "integrity")]
+mod integrity;
+
 mod metadata;
 
 mod serde_impl;
 
-pub use envelope::{Envelope, IntoEnvelope};
-pub use metadata::MetaData;
-pub use serde_impl::*;
+#[cfg(feature = "stream")]
+mod stream;
+
+mod trace_context;
+
+pub use batch::{BatchLineage, EnvelopeBatch, TimeWindow};
+#[cfg(feature = "http")]
+pub use codec::HttpHeaderCodec;
+pub use codec::{
+    AmqpProperties, AmqpPropertiesCodec, KafkaHeaderCodec, KafkaHeaders, MetaDataCodec, MetaDataCodecError,
+    MetaDataKeys,
+};
+pub use envelope::{Envelope, Failed, IntoEnvelope};
+pub use extensions::{Extension, Extensions};
+#[cfg(feature = "integrity")]
+pub use integrity::{IntegrityError, Seal};
+pub use metadata::{IntoMetaData, MergeError, MergePolicy, MetaData, MetaDataError, Strictness, TryIntoMetaData};
+#[cfg(feature = "stream")]
+pub use stream::{EnvelopeStreamExt, EnvelopingStreamExt};
+pub use trace_context::{TraceContext, TraceContextError, TraceParent, TraceState};
 
 /// Type has correlation identifier.
 pub trait Correlation {
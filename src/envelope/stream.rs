@@ -0,0 +1,195 @@
+//! Combinators over the contents of streams of envelopes, via the `stream` feature.
+//!
+//! Each combinator carries the metadata of an envelope along to its new content, relabeled for the
+//! new content type as [`Envelope::map`] does.
+
+use std::future::{self, Future};
+
+use futures::{Stream, StreamExt};
+
+use crate::envelope::Envelope;
+use crate::Label;
+
+/// Extends streams of envelopes with combinators over their contents.
+pub trait EnvelopeStreamExt<T>: Stream<Item = Envelope<T>> + Sized
+where
+    T: Label + Send,
+{
+    /// Maps the content of each envelope.
+    fn map_content<U, F>(self, mut f: F) -> impl Stream<Item = Envelope<U>>
+    where
+        U: Label + Send,
+        F: FnMut(T) -> U,
+    {
+        self.map(move |envelope| envelope.map(&mut f))
+    }
+
+    /// Maps the content of each envelope via an asynchronous function, in stream order.
+    fn and_then_content<U, Fut, F>(self, mut f: F) -> impl Stream<Item = Envelope<U>>
+    where
+        U: Label + Send,
+        Fut: Future<Output = U>,
+        F: FnMut(T) -> Fut,
+    {
+        self.then(move |envelope| {
+            let (metadata, content) = envelope.into_parts();
+            let content = f(content);
+            async move { Envelope::from_parts(metadata.unchecked_relabel(), content.await) }
+        })
+    }
+
+    /// Maps the content of each envelope via a fallible function, transposing the resulting
+    /// `Envelope<Result<U, E>>` into a `Result<Envelope<U>, E>`; see [`Envelope::transpose`].
+    fn try_map_content<U, E, F>(self, mut f: F) -> impl Stream<Item = Result<Envelope<U>, E>>
+    where
+        U: Label + Send,
+        E: Send,
+        F: FnMut(T) -> Result<U, E>,
+    {
+        self.map(move |envelope| envelope.map(&mut f).transpose())
+    }
+
+    /// Keeps the envelopes whose content satisfies the predicate.
+    fn filter_content<F>(self, mut predicate: F) -> impl Stream<Item = Envelope<T>>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.filter(move |envelope| future::ready(predicate(envelope.as_ref())))
+    }
+}
+
+impl<T, S> EnvelopeStreamExt<T> for S
+where
+    T: Label + Send,
+    S: Stream<Item = Envelope<T>>,
+{
+}
+
+/// Extends streams of raw items with [`EnvelopingStreamExt::enveloped`].
+pub trait EnvelopingStreamExt: Stream + Sized
+where
+    Self::Item: Label + Send,
+{
+    /// Wraps each item in a new envelope, starting a message flow; see [`Envelope::new`].
+    fn enveloped(self) -> impl Stream<Item = Envelope<Self::Item>> {
+        self.map(Envelope::new)
+    }
+}
+
+impl<S> EnvelopingStreamExt for S
+where
+    S: Stream,
+    S::Item: Label + Send,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::stream;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::envelope::{Correlation, MetaData};
+    use crate::{Id, MakeLabeling};
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Reading(i32);
+
+    impl Label for Reading {
+        type Labeler = MakeLabeling<Self>;
+
+        fn labeler() -> Self::Labeler {
+            MakeLabeling::default()
+        }
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Celsius(f64);
+
+    impl Label for Celsius {
+        type Labeler = MakeLabeling<Self>;
+
+        fn labeler() -> Self::Labeler {
+            MakeLabeling::default()
+        }
+    }
+
+    fn readings() -> Vec<Envelope<Reading>> {
+        (1..=4).map(|r| Envelope::new(Reading(r))).collect()
+    }
+
+    fn ids<T: Label + Send + Sync>(envelopes: &[Envelope<T>]) -> Vec<(i64, i64)> {
+        envelopes
+            .iter()
+            .map(|e| (e.correlation().num(), e.metadata().message_id().num()))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_map_content() {
+        let readings = readings();
+        let expected = ids(&readings);
+
+        let actual: Vec<Envelope<Celsius>> = stream::iter(readings)
+            .map_content(|r| Celsius(f64::from(r.0) / 2.0))
+            .collect()
+            .await;
+        assert_eq!(ids(&actual), expected);
+        assert_eq!(actual[1].as_ref(), &Celsius(1.0));
+        let correlation: &Id<Celsius> = actual[0].metadata().correlation();
+        assert_eq!(correlation.label(), "Celsius");
+    }
+
+    #[tokio::test]
+    async fn test_and_then_content() {
+        let readings = readings();
+        let expected = ids(&readings);
+
+        let actual: Vec<Envelope<Celsius>> = stream::iter(readings)
+            .and_then_content(|r| async move { Celsius(f64::from(r.0)) })
+            .collect()
+            .await;
+        assert_eq!(ids(&actual), expected);
+        assert_eq!(actual[3].as_ref(), &Celsius(4.0));
+    }
+
+    #[tokio::test]
+    async fn test_try_map_content() {
+        let readings = readings();
+        let expected = ids(&readings);
+
+        let actual: Vec<Result<Envelope<Celsius>, String>> = stream::iter(readings)
+            .try_map_content(|r| {
+                if r.0 % 2 == 0 {
+                    Ok(Celsius(f64::from(r.0)))
+                } else {
+                    Err(format!("odd reading: {}", r.0))
+                }
+            })
+            .collect()
+            .await;
+        assert_eq!(actual[0].as_ref().unwrap_err(), "odd reading: 1");
+        let ok: Vec<_> = actual.into_iter().filter_map(Result::ok).collect();
+        assert_eq!(ids(&ok), vec![expected[1], expected[3]]);
+    }
+
+    #[tokio::test]
+    async fn test_filter_content() {
+        let readings = readings();
+        let expected = ids(&readings);
+
+        let actual: Vec<Envelope<Reading>> = stream::iter(readings).filter_content(|r| r.0 > 2).collect().await;
+        assert_eq!(ids(&actual), expected[2..].to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_enveloped() {
+        let actual: Vec<Envelope<Reading>> = stream::iter((1..=3).map(Reading)).enveloped().collect().await;
+        assert_eq!(actual.len(), 3);
+        assert_eq!(actual[2].as_ref(), &Reading(3));
+        for envelope in &actual {
+            let metadata: &MetaData<Reading> = envelope.metadata();
+            assert_eq!(metadata.message_id(), metadata.correlation());
+        }
+    }
+}
@@ -0,0 +1,376 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const MAX_TRACE_STATE_ENTRIES: usize = 32;
+const MAX_TRACE_STATE_KEY_LENGTH: usize = 256;
+const MAX_TRACE_STATE_VALUE_LENGTH: usize = 256;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum TraceContextError {
+    #[error("Invalid traceparent: {0:?}")]
+    InvalidTraceParent(String),
+
+    #[error("Invalid tracestate entry: {0:?}")]
+    InvalidTraceStateEntry(String),
+
+    #[error("Duplicate tracestate key: {0:?}")]
+    DuplicateTraceStateKey(String),
+
+    #[error("tracestate has more than {MAX_TRACE_STATE_ENTRIES} entries: {0}")]
+    TooManyTraceStateEntries(usize),
+}
+
+/// The W3C [trace context](https://www.w3.org/TR/trace-context/) of a message, propagated with its
+/// envelope across services.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceContext {
+    traceparent: TraceParent,
+    #[serde(default)]
+    tracestate: TraceState,
+}
+
+impl TraceContext {
+    pub const fn new(traceparent: TraceParent, tracestate: TraceState) -> Self {
+        Self { traceparent, tracestate }
+    }
+
+    /// Parses the `traceparent` and `tracestate` header values. Per the specification, a
+    /// `tracestate` is only meaningful with a valid `traceparent`.
+    pub fn parse(traceparent: &str, tracestate: Option<&str>) -> Result<Self, TraceContextError> {
+        let traceparent = traceparent.parse()?;
+        let tracestate = tracestate.map_or_else(|| Ok(TraceState::default()), str::parse)?;
+        Ok(Self { traceparent, tracestate })
+    }
+
+    pub const fn traceparent(&self) -> &TraceParent {
+        &self.traceparent
+    }
+
+    pub const fn tracestate(&self) -> &TraceState {
+        &self.tracestate
+    }
+}
+
+/// The `traceparent` of a W3C trace context: `{version}-{trace-id}-{parent-id}-{trace-flags}`,
+/// in lowercase hex; e.g., `00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TraceParent {
+    trace_id: u128,
+    parent_id: u64,
+    flags: u8,
+}
+
+impl TraceParent {
+    const VERSION: u8 = 0;
+    const SAMPLED_FLAG: u8 = 0x01;
+
+    /// Creates a version 00 traceparent. All-zero trace and parent ids are invalid.
+    pub fn new(trace_id: u128, parent_id: u64, sampled: bool) -> Result<Self, TraceContextError> {
+        if trace_id == 0 || parent_id == 0 {
+            return Err(TraceContextError::InvalidTraceParent(format!(
+                "{:02x}-{trace_id:032x}-{parent_id:016x}-00",
+                Self::VERSION
+            )));
+        }
+
+        let flags = if sampled { Self::SAMPLED_FLAG } else { 0 };
+        Ok(Self { trace_id, parent_id, flags })
+    }
+
+    pub const fn trace_id(&self) -> u128 {
+        self.trace_id
+    }
+
+    /// The id of the caller's span, which becomes the parent of spans opened for the message.
+    pub const fn parent_id(&self) -> u64 {
+        self.parent_id
+    }
+
+    pub const fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    pub const fn is_sampled(&self) -> bool {
+        self.flags & Self::SAMPLED_FLAG != 0
+    }
+}
+
+impl fmt::Display for TraceParent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:02x}-{:032x}-{:016x}-{:02x}",
+            Self::VERSION,
+            self.trace_id,
+            self.parent_id,
+            self.flags
+        )
+    }
+}
+
+fn parse_hex<const N: usize>(field: &str) -> Option<&str> {
+    let is_lower_hex = |b: u8| b.is_ascii_digit() || (b'a'..=b'f').contains(&b);
+    (field.len() == N && field.bytes().all(is_lower_hex)).then_some(field)
+}
+
+impl FromStr for TraceParent {
+    type Err = TraceContextError;
+
+    /// Parses a traceparent. Versions after 00 are parsed as version 00, ignoring any trailing
+    /// fields, as the specification requires.
+    fn from_str(rep: &str) -> Result<Self, Self::Err> {
+        let invalid = || TraceContextError::InvalidTraceParent(rep.to_string());
+        let mut fields = rep.trim().split('-');
+
+        let version = fields
+            .next()
+            .and_then(parse_hex::<2>)
+            .and_then(|v| u8::from_str_radix(v, 16).ok())
+            .ok_or_else(invalid)?;
+        let trace_id = fields
+            .next()
+            .and_then(parse_hex::<32>)
+            .and_then(|id| u128::from_str_radix(id, 16).ok())
+            .ok_or_else(invalid)?;
+        let parent_id = fields
+            .next()
+            .and_then(parse_hex::<16>)
+            .and_then(|id| u64::from_str_radix(id, 16).ok())
+            .ok_or_else(invalid)?;
+        let flags = fields
+            .next()
+            .and_then(parse_hex::<2>)
+            .and_then(|f| u8::from_str_radix(f, 16).ok())
+            .ok_or_else(invalid)?;
+
+        let has_trailing_fields = fields.next().is_some();
+        if version == 0xff || (version == Self::VERSION && has_trailing_fields) {
+            return Err(invalid());
+        }
+
+        Self::new(trace_id, parent_id, false)
+            .map(|traceparent| Self { flags, ..traceparent })
+            .map_err(|_| invalid())
+    }
+}
+
+impl From<TraceParent> for String {
+    fn from(traceparent: TraceParent) -> Self {
+        traceparent.to_string()
+    }
+}
+
+impl TryFrom<String> for TraceParent {
+    type Error = TraceContextError;
+
+    fn try_from(rep: String) -> Result<Self, Self::Error> {
+        rep.parse()
+    }
+}
+
+/// The `tracestate` of a W3C trace context: vendor-specific `key=value` entries, most recently
+/// updated first; e.g., `congo=t61rcWkgMzE,rojo=00f067aa0ba902b7`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct TraceState {
+    entries: Vec<(String, String)>,
+}
+
+impl TraceState {
+    pub const fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.iter().find_map(|(k, v)| (k == key).then_some(v.as_str()))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Adds or updates an entry, moving it to the front as the specification requires. The oldest
+    /// entry is dropped if the tracestate would exceed 32 entries.
+    pub fn insert(&mut self, key: &str, value: &str) -> Result<(), TraceContextError> {
+        if !is_valid_key(key) || !is_valid_value(value) {
+            return Err(TraceContextError::InvalidTraceStateEntry(format!("{key}={value}")));
+        }
+
+        self.entries.retain(|(k, _)| k != key);
+        self.entries.insert(0, (key.to_string(), value.to_string()));
+        self.entries.truncate(MAX_TRACE_STATE_ENTRIES);
+        Ok(())
+    }
+}
+
+fn is_valid_key(key: &str) -> bool {
+    let is_key_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || "_-*/".contains(c);
+    let is_simple_key =
+        |k: &str| k.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit()) && k.chars().all(is_key_char);
+
+    if key.is_empty() || MAX_TRACE_STATE_KEY_LENGTH < key.len() {
+        return false;
+    }
+
+    // Multi-tenant keys take the form `tenant@system`.
+    match key.split_once('@') {
+        None => is_simple_key(key),
+        Some((tenant, system)) => is_simple_key(tenant) && is_simple_key(system) && system.len() <= 14,
+    }
+}
+
+fn is_valid_value(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_TRACE_STATE_VALUE_LENGTH
+        && !value.ends_with(' ')
+        && value.chars().all(|c| matches!(c, ' '..='~') && c != ',' && c != '=')
+}
+
+impl fmt::Display for TraceState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (key, value)) in self.entries.iter().enumerate() {
+            if 0 < i {
+                f.write_str(",")?;
+            }
+            write!(f, "{key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TraceState {
+    type Err = TraceContextError;
+
+    /// Parses a tracestate, skipping empty list members.
+    fn from_str(rep: &str) -> Result<Self, Self::Err> {
+        let mut entries: Vec<(String, String)> = Vec::new();
+        for member in rep.split(',').map(|m| m.trim_matches(|c| c == ' ' || c == '\t')) {
+            if member.is_empty() {
+                continue;
+            }
+
+            let (key, value) = member
+                .split_once('=')
+                .filter(|(k, v)| is_valid_key(k) && is_valid_value(v))
+                .ok_or_else(|| TraceContextError::InvalidTraceStateEntry(member.to_string()))?;
+
+            if entries.iter().any(|(k, _)| k == key) {
+                return Err(TraceContextError::DuplicateTraceStateKey(key.to_string()));
+            }
+            entries.push((key.to_string(), value.to_string()));
+        }
+
+        if MAX_TRACE_STATE_ENTRIES < entries.len() {
+            return Err(TraceContextError::TooManyTraceStateEntries(entries.len()));
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+impl From<TraceState> for String {
+    fn from(tracestate: TraceState) -> Self {
+        tracestate.to_string()
+    }
+}
+
+impl TryFrom<String> for TraceState {
+    type Error = TraceContextError;
+
+    fn try_from(rep: String) -> Result<Self, Self::Error> {
+        rep.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::*;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    #[test]
+    fn test_parse_traceparent() {
+        let actual: TraceParent = assert_ok!(TRACEPARENT.parse());
+        assert_eq!(actual.trace_id(), 0x4bf92f3577b34da6a3ce929d0e0e4736);
+        assert_eq!(actual.parent_id(), 0x00f067aa0ba902b7);
+        assert!(actual.is_sampled());
+        assert_eq!(actual.to_string(), TRACEPARENT);
+
+        let future: TraceParent = assert_ok!("cc-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00-future".parse());
+        assert!(!future.is_sampled());
+        assert_eq!(
+            future.to_string(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-00"
+        );
+    }
+
+    #[test]
+    fn test_invalid_traceparent() {
+        for rep in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-4BF92F3577B34DA6A3CE929D0E0E4736-00f067aa0ba902b7-01",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e473-600f067aa0ba902b7-01",
+        ] {
+            let err = assert_err!(rep.parse::<TraceParent>());
+            assert_eq!(err, TraceContextError::InvalidTraceParent(rep.to_string()));
+        }
+        assert_err!(TraceParent::new(0, 1, true));
+    }
+
+    #[test]
+    fn test_parse_tracestate() {
+        let mut actual: TraceState = assert_ok!("congo=t61rcWkgMzE, ,rojo=00f067aa0ba902b7,fw529a3039@dt=ab".parse());
+        assert_eq!(actual.get("rojo"), Some("00f067aa0ba902b7"));
+        assert_eq!(actual.get("fw529a3039@dt"), Some("ab"));
+        assert_eq!(
+            actual.to_string(),
+            "congo=t61rcWkgMzE,rojo=00f067aa0ba902b7,fw529a3039@dt=ab"
+        );
+
+        assert_ok!(actual.insert("rojo", "00f067aa0ba902b8"));
+        assert_eq!(
+            actual.to_string(),
+            "rojo=00f067aa0ba902b8,congo=t61rcWkgMzE,fw529a3039@dt=ab"
+        );
+        assert_err!(actual.insert("Rojo", "1"));
+        assert_err!(actual.insert("rojo", "a=b"));
+
+        assert!(assert_ok!("".parse::<TraceState>()).is_empty());
+        assert_eq!(
+            assert_err!("rojo=1,rojo=2".parse::<TraceState>()),
+            TraceContextError::DuplicateTraceStateKey("rojo".to_string())
+        );
+        assert_err!("rojo".parse::<TraceState>());
+        assert_err!("rojo=a=b".parse::<TraceState>());
+
+        let too_many = (0..33).map(|i| format!("k{i}=v")).collect::<Vec<_>>().join(",");
+        assert_eq!(
+            assert_err!(too_many.parse::<TraceState>()),
+            TraceContextError::TooManyTraceStateEntries(33)
+        );
+    }
+
+    #[test]
+    fn test_trace_context_serde() {
+        let context = assert_ok!(TraceContext::parse(TRACEPARENT, Some("rojo=00f067aa0ba902b7")));
+        let json = assert_ok!(serde_json::to_string(&context));
+        assert_eq!(
+            json,
+            format!(r#"{{"traceparent":"{TRACEPARENT}","tracestate":"rojo=00f067aa0ba902b7"}}"#)
+        );
+        assert_eq!(assert_ok!(serde_json::from_str::<TraceContext>(&json)), context);
+        assert_err!(serde_json::from_str::<TraceContext>(r#"{"traceparent":"00-00"}"#));
+    }
+}
@@ -1,5 +1,7 @@
 use crate::{AlphabetCodec, Id, IdPrettifier, Label, LabeledRealtimeIdGenerator, MachineNode};
 use once_cell::sync::Lazy;
+use std::ops::{RangeBounds, RangeInclusive};
+use std::time::SystemTime;
 
 pub type CommonIdGenerator<T> = LabeledRealtimeIdGenerator<T>;
 
@@ -10,9 +12,11 @@ static ID_GENERATOR: Lazy<std::sync::RwLock<Option<CommonIdGenerator<()>>>> =
 
 static DEFAULT_PRETTIFIER: Lazy<IdPrettifier<AlphabetCodec>> = Lazy::new(IdPrettifier::default);
 
-/// Set the ID_GENERATOR to be used by `next_id()`. The `gen` argument may be set up given the
-/// assigned `MachineNode` for the process, facilitating uniqueness across nodes in a distributed
-/// system. Other configuration, such as prettifier codec, should be consistent across nodes.
+/// Set the ID_GENERATOR to be used by `next_id()`.
+///
+/// The `gen` argument may be set up given the assigned `MachineNode` for the process, facilitating
+/// uniqueness across nodes in a distributed system. Other configuration, such as prettifier codec,
+/// should be consistent across nodes.
 pub fn set_id_generator(gen: CommonIdGenerator<()>) {
     let mut generator = ID_GENERATOR.write().unwrap();
     *generator = Some(gen);
@@ -32,15 +36,21 @@ pub fn next_id<T: Label>() -> Id<T> {
 
     #[allow(clippy::option_if_let_else)]
     let id = match &*guard {
-        Some(g) => g.next_id(),
+        Some(g) => g.next_id_as(),
         None => {
             drop(guard);
             let g = CommonIdGenerator::distributed(MachineNode::default(), prettifier());
-            let id = g.next_id();
+            let id = g.next_id_as();
             set_id_generator(g);
             id
         },
     };
 
-    id.relabel()
+    id
+}
+
+/// The bounds of the ids of `T` that may be generated within a time range, pretty printed with the
+/// prettifier of the configured generator; e.g., to query the orders placed yesterday.
+pub fn id_range<T: Label, Ts: Copy + Into<SystemTime>>(range: impl RangeBounds<Ts>) -> RangeInclusive<Id<T>> {
+    Id::range_for(range, &prettifier())
 }
@@ -0,0 +1,233 @@
+//! Alternative serde representations for `Id<T>`, for use with `#[serde(with = "...")]`.
+//!
+//! By default `Id<T>` serializes as a struct carrying both the `snowflake` and `pretty` forms. The
+//! modules here serialize a single form instead, and deserialize back to a full `Id<T>` using the
+//! prettifier configured in the [`generator`](crate::generator) module.
+//!
+//! ```rust
+//! use pretty_snowflake::{Id, Label};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Label)]
+//! struct Order;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct OrderRow {
+//!     #[serde(with = "pretty_snowflake::id_serde::number")]
+//!     id: Id<Order>,
+//! }
+//! ```
+
+use crate::{generator, Id, Label};
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+fn id_from_seed<T: Label + ?Sized>(seed: i64) -> Id<T> {
+    Id::from_snowflake(seed, &generator::prettifier())
+}
+
+fn id_from_pretty<T: Label + ?Sized, E: de::Error>(pretty: &str) -> Result<Id<T>, E> {
+    let prettifier = generator::prettifier();
+    let seed = prettifier.to_id_seed(pretty).map_err(de::Error::custom)?;
+    Ok(Id::from_snowflake(seed, &prettifier))
+}
+
+/// Represents an `Id<T>` by its pretty string only; e.g., `"HPJD-72036-HAPK-58077"`.
+///
+/// If `T` supplies a prefix, the pretty string is prefixed; e.g., `"ord_HPJD-72036-HAPK-58077"`,
+/// and deserialization requires the prefix.
+pub mod pretty {
+    use super::*;
+
+    pub fn serialize<T: ?Sized, S: Serializer>(id: &Id<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&id.prefixed_pretty())
+    }
+
+    pub fn deserialize<'de, T: Label + ?Sized, D: Deserializer<'de>>(deserializer: D) -> Result<Id<T>, D::Error> {
+        let rep = String::deserialize(deserializer)?;
+        let pretty = Id::<T>::strip_prefix(&rep).map_err(de::Error::custom)?;
+        id_from_pretty(pretty)
+    }
+}
+
+/// Represents an `Id<T>` by its snowflake number only; e.g., `9223372036854775807`.
+pub mod number {
+    use super::*;
+
+    pub fn serialize<T: ?Sized, S: Serializer>(id: &Id<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(id.num())
+    }
+
+    pub fn deserialize<'de, T: Label + ?Sized, D: Deserializer<'de>>(deserializer: D) -> Result<Id<T>, D::Error> {
+        let seed = i64::deserialize(deserializer)?;
+        Ok(id_from_seed(seed))
+    }
+}
+
+/// Represents an `Id<T>` by its snowflake number rendered as a string; e.g.,
+/// `"9223372036854775807"`. Useful for JavaScript clients, which cannot represent the full `i64`
+/// range as a number.
+pub mod number_string {
+    use super::*;
+
+    pub fn serialize<T: ?Sized, S: Serializer>(id: &Id<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&id.num())
+    }
+
+    pub fn deserialize<'de, T: Label + ?Sized, D: Deserializer<'de>>(deserializer: D) -> Result<Id<T>, D::Error> {
+        let rep = String::deserialize(deserializer)?;
+        let seed: i64 = rep.parse().map_err(de::Error::custom)?;
+        Ok(id_from_seed(seed))
+    }
+}
+
+/// Represents an `Id<T>` by its labeled pretty string, as shown by `Display`; e.g.,
+/// `"Order::HPJD-72036-HAPK-58077"`.
+///
+/// Unlabeled ids are represented by the pretty string alone, and ids of types supplying a prefix by
+/// the prefixed pretty string. Deserialization rejects a label or prefix that does not match that
+/// of `T`.
+pub mod labeled {
+    use super::*;
+
+    pub fn serialize<T: ?Sized, S: Serializer>(id: &Id<T>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(id)
+    }
+
+    pub fn deserialize<'de, T: Label + ?Sized, D: Deserializer<'de>>(deserializer: D) -> Result<Id<T>, D::Error> {
+        let rep = String::deserialize(deserializer)?;
+        if <T as Label>::prefix().is_some() {
+            let pretty = Id::<T>::strip_prefix(&rep).map_err(de::Error::custom)?;
+            return id_from_pretty(pretty);
+        }
+
+        let expected = <T as Label>::label();
+        let (label, pretty) = rep.rsplit_once("::").unwrap_or(("", rep.as_str()));
+        if label != expected {
+            return Err(de::Error::custom(format!(
+                "expected id label \"{expected}\" but found \"{label}\""
+            )));
+        }
+
+        id_from_pretty(pretty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CustomLabeling, NoLabeling};
+    use pretty_assertions::assert_eq;
+    use serde::Serialize;
+    use serde_test::{assert_de_tokens_error, assert_tokens, Token};
+
+    const SNOWFLAKE: i64 = 824227036833910784;
+    const PRETTY: &str = "ARPJ-27036-GVQS-07849";
+
+    #[derive(Debug, PartialEq)]
+    struct Order;
+
+    impl Label for Order {
+        type Labeler = CustomLabeling;
+
+        fn labeler() -> Self::Labeler {
+            CustomLabeling::new("Order")
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Anonymous;
+
+    impl Label for Anonymous {
+        type Labeler = NoLabeling;
+
+        fn labeler() -> Self::Labeler {
+            NoLabeling
+        }
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Row {
+        #[serde(with = "pretty")]
+        pretty: Id<Order>,
+        #[serde(with = "number")]
+        number: Id<Order>,
+        #[serde(with = "number_string")]
+        number_string: Id<Order>,
+        #[serde(with = "labeled")]
+        labeled: Id<Order>,
+        #[serde(with = "labeled")]
+        unlabeled: Id<Anonymous>,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Labeled {
+        #[serde(with = "labeled")]
+        id: Id<Order>,
+    }
+
+    #[test]
+    fn test_id_serde_representations() {
+        let id: Id<Order> = Id::direct("Order", SNOWFLAKE, PRETTY);
+        let row = Row {
+            pretty: id.clone(),
+            number: id.clone(),
+            number_string: id.clone(),
+            labeled: id,
+            unlabeled: Id::direct("", SNOWFLAKE, PRETTY),
+        };
+
+        assert_tokens(
+            &row,
+            &[
+                Token::Struct { name: "Row", len: 5 },
+                Token::Str("pretty"),
+                Token::Str(PRETTY),
+                Token::Str("number"),
+                Token::I64(SNOWFLAKE),
+                Token::Str("number_string"),
+                Token::Str("824227036833910784"),
+                Token::Str("labeled"),
+                Token::Str("Order::ARPJ-27036-GVQS-07849"),
+                Token::Str("unlabeled"),
+                Token::Str(PRETTY),
+                Token::StructEnd,
+            ],
+        );
+    }
+
+    #[test]
+    fn test_deserialize_rebuilds_full_id() {
+        let actual: Id<Order> = id_from_pretty::<_, de::value::Error>(PRETTY).unwrap();
+        assert_eq!(actual.num(), SNOWFLAKE);
+        assert_eq!(actual.pretty(), PRETTY);
+        assert_eq!(actual.label(), "Order");
+
+        let actual: Id<Order> = id_from_seed(SNOWFLAKE);
+        assert_eq!(actual.pretty(), PRETTY);
+        assert_eq!(actual.label(), "Order");
+    }
+
+    #[test]
+    fn test_deserialize_labeled_mismatch() {
+        assert_de_tokens_error::<Labeled>(
+            &[
+                Token::Struct { name: "Labeled", len: 1 },
+                Token::Str("id"),
+                Token::Str("User::ARPJ-27036-GVQS-07849"),
+            ],
+            "expected id label \"Order\" but found \"User\"",
+        );
+    }
+
+    #[test]
+    fn test_deserialize_invalid_pretty() {
+        assert_de_tokens_error::<Labeled>(
+            &[
+                Token::Struct { name: "Labeled", len: 1 },
+                Token::Str("id"),
+                Token::Str("Order::ARPJ-27036-GVQS-07840"),
+            ],
+            "Not a valid ID: ARPJ-27036-GVQS-07840",
+        );
+    }
+}
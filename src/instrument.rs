@@ -0,0 +1,98 @@
+//! Records id generation via the optional `metrics` and `tracing` features.
+//!
+//! Metrics recorded, labeled by generator `strategy` and, for ids, by type `label`:
+//!
+//! - `pretty_snowflake_ids_generated_total`: counter of generated ids.
+//! - `pretty_snowflake_sequence_exhausted_total`: counter of rollovers of the 4096-id
+//!   per-millisecond sequence, after which the generator may wait for the next millisecond.
+//! - `pretty_snowflake_wait_seconds`: histogram of the time spent generating an id upon a sequence
+//!   rollover.
+//! - `pretty_snowflake_clock_regressions_total`: counter of ids generated for an earlier
+//!   millisecond than their predecessor, because the system clock moved backwards.
+//! - `pretty_snowflake_clock_regression_seconds`: histogram of how far back the clock moved.
+//! - `pretty_snowflake_prettify_seconds`: histogram of `IdPrettifier::prettify` latency.
+
+use std::time::Duration;
+
+use crate::snowflake::Id as SnowflakeId;
+
+pub const IDS_GENERATED: &str = "pretty_snowflake_ids_generated_total";
+pub const SEQUENCE_EXHAUSTED: &str = "pretty_snowflake_sequence_exhausted_total";
+pub const WAIT_SECONDS: &str = "pretty_snowflake_wait_seconds";
+pub const CLOCK_REGRESSIONS: &str = "pretty_snowflake_clock_regressions_total";
+pub const CLOCK_REGRESSION_SECONDS: &str = "pretty_snowflake_clock_regression_seconds";
+pub const PRETTIFY_SECONDS: &str = "pretty_snowflake_prettify_seconds";
+
+/// Records an id generated after `previous` by the same worker, taking `elapsed` to generate.
+pub(crate) fn record_next_id(
+    strategy: &'static str, label: &'static str, previous: SnowflakeId, id: SnowflakeId, elapsed: Duration,
+) {
+    #[cfg(feature = "metrics")]
+    metrics::counter!(IDS_GENERATED, "strategy" => strategy, "label" => label).increment(1);
+    #[cfg(feature = "tracing")]
+    tracing::trace!(strategy, label, %id, "generated snowflake id");
+
+    if id < previous {
+        let regression = Duration::from_millis((previous.millis() - id.millis()).unsigned_abs());
+
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!(CLOCK_REGRESSIONS, "strategy" => strategy).increment(1);
+            metrics::histogram!(CLOCK_REGRESSION_SECONDS, "strategy" => strategy).record(regression);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::warn!(strategy, ?regression, %previous, %id, "clock moved backwards while generating snowflake ids");
+    } else if previous.sequence() == SnowflakeId::MAX_SEQUENCE && id.sequence() == 0 {
+        #[cfg(feature = "metrics")]
+        {
+            metrics::counter!(SEQUENCE_EXHAUSTED, "strategy" => strategy).increment(1);
+            metrics::histogram!(WAIT_SECONDS, "strategy" => strategy).record(elapsed);
+        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            strategy,
+            ?elapsed,
+            "snowflake sequence exhausted; waited for the next millisecond"
+        );
+    }
+}
+
+pub(crate) fn record_prettify(elapsed: Duration) {
+    #[cfg(feature = "metrics")]
+    metrics::histogram!(PRETTIFY_SECONDS).record(elapsed);
+    #[cfg(feature = "tracing")]
+    tracing::trace!(?elapsed, "prettified snowflake id");
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_record_clock_regression() {
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let previous = SnowflakeId::from(1_005 << 22);
+        let id = SnowflakeId::from((1_000 << 22) + 1);
+
+        metrics::with_local_recorder(&recorder, || {
+            record_next_id("real_time", "", previous, id, Duration::ZERO);
+        });
+
+        let recorded: Vec<_> = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .map(|(key, _, _, value)| (key.key().name().to_string(), value))
+            .collect();
+        assert!(recorded.contains(&(CLOCK_REGRESSIONS.to_string(), DebugValue::Counter(1))));
+        assert!(recorded.iter().any(|(name, value)| {
+            name == CLOCK_REGRESSION_SECONDS && matches!(value, DebugValue::Histogram(v) if v[0] == 0.005)
+        }));
+        assert!(!recorded.iter().any(|(name, _)| name == SEQUENCE_EXHAUSTED));
+        assert_eq!(recorded.len(), 3);
+    }
+}
@@ -1,9 +1,114 @@
 use crate::{CustomLabeling, Labeling, MakeLabeling, NoLabeling};
-use std::collections::HashMap;
+use once_cell::sync::Lazy;
+use std::any::TypeId;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::rc::Rc;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant, SystemTime};
 
+/// Labels a type for its ids; e.g., the `Order` in `Order::HPJD-72036-HAPK-58077`.
+///
+/// Labels provided by this crate follow a stable grammar, so that they do not change across
+/// crate or compiler versions:
+///
+/// - Primitives, `str`, `&str`, `String` and the `std::time` types are labeled by their unqualified name;
+///   e.g., `i64`, `String` or `Duration`. The unit type `()` is not labeled, but as a type
+///   parameter, it and any other unlabeled type render as `()`; e.g., `Vec<()>`.
+/// - `Option`, `Result`, `Box`, `Rc`, `Arc` and `Cow` are transparent, and take the label of the
+///   type they wrap; e.g., `Option<Box<i64>>` is labeled `i64`.
+/// - Collections are labeled by their unqualified name followed by the labels of their type
+///   parameters, separated by commas without whitespace; e.g., `Vec<i64>` or
+///   `HashMap<String,Vec<u8>>`. Hasher parameters are omitted.
+/// - Tuples and arrays use Rust's syntax, without whitespace; e.g., `(i64,String)`, `(i64,)` or
+///   `[u8;16]`. Slices are labeled like `[u8]`.
 pub trait Label {
     type Labeler: Labeling + Clone;
     fn labeler() -> Self::Labeler;
+
+    /// The label of the type, without allocating.
+    ///
+    /// By default, the label is computed via `labeler()` on first use and interned for the life of
+    /// the program. `#[derive(Label)]` overrides this with a string literal for non-generic types,
+    /// which avoids the interning lookup altogether.
+    fn label() -> &'static str {
+        intern_label(typeid::of::<Self>(), || Self::labeler().label())
+    }
+
+    /// A short, stable code identifying the type in the string form of its ids, Stripe-style; e.g.,
+    /// `ord` for `ord_HPJD-72036-HAPK-58077`. By default, types have no prefix.
+    ///
+    /// When a type has a prefix, it is included in `String::from(Id<T>)`, `Display` and
+    /// serialization, and it is required when parsing or deserializing an `Id<T>`.
+    fn prefix() -> Option<&'static str> {
+        None
+    }
+}
+
+/// Declares that ids of `A` may be relabeled as ids of `Self` via
+/// [`Id::relabel`](crate::Id::relabel); e.g., `impl RelabelFrom<Order> for OrderSummary {}`.
+///
+/// Every type may be relabeled from itself, and the transparent wrappers `Option`, `Result`, `Box`,
+/// `Rc` and `Arc` may be relabeled to and from the type they wrap. Other conversions must be
+/// declared, or else made explicitly via [`Id::unchecked_relabel`](crate::Id::unchecked_relabel).
+pub trait RelabelFrom<A: ?Sized> {}
+
+impl<T: ?Sized> RelabelFrom<T> for T {}
+
+macro_rules! transparent_relabel {
+    ($($t:ident)::+ $(, $extra:ident)?) => {
+        impl<T $(, $extra)?> RelabelFrom<T> for $($t)::+<T $(, $extra)?> {}
+        impl<T $(, $extra)?> RelabelFrom<$($t)::+<T $(, $extra)?>> for T {}
+    };
+}
+
+transparent_relabel!(Option);
+transparent_relabel!(Result, E);
+transparent_relabel!(Box);
+transparent_relabel!(Rc);
+transparent_relabel!(Arc);
+
+// Labels are interned by type id, since type names are not guaranteed to be unique. Unlike
+// `TypeId::of`, `typeid::of` supports non-'static types such as `&str`, treating types that differ
+// only in lifetimes as the same type, as their labels do. The set of labeled types is fixed at
+// compile time, so the memory leaked for owned labels is bounded.
+static LABELS: Lazy<RwLock<HashMap<TypeId, &'static str>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+fn intern_label(type_id: TypeId, make_label: impl FnOnce() -> Cow<'static, str>) -> &'static str {
+    if let Some(label) = LABELS.read().unwrap().get(&type_id) {
+        return label;
+    }
+
+    // Computed before taking the write lock, since labels of generic types intern their parameters.
+    let label = make_label();
+    let mut labels = LABELS.write().unwrap();
+    labels.entry(type_id).or_insert_with(|| match label {
+        Cow::Borrowed(label) => label,
+        Cow::Owned(label) => Box::leak(label.into_boxed_str()),
+    })
+}
+
+// Labels of custom labelings are interned by value, so that generators recording them in metrics
+// need not allocate per id. A metrics recorder retains each distinct label anyway.
+static CUSTOM_LABELS: Lazy<RwLock<HashSet<&'static str>>> = Lazy::new(|| RwLock::new(HashSet::new()));
+
+pub fn intern_custom_label(label: Cow<'static, str>) -> &'static str {
+    let label = match label {
+        Cow::Borrowed(label) => return label,
+        Cow::Owned(label) => label,
+    };
+
+    if let Some(interned) = CUSTOM_LABELS.read().unwrap().get(label.as_str()) {
+        return interned;
+    }
+
+    let mut labels = CUSTOM_LABELS.write().unwrap();
+    if let Some(interned) = labels.get(label.as_str()) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(label.into_boxed_str());
+    labels.insert(interned);
+    interned
 }
 
 impl Label for () {
@@ -12,6 +117,10 @@ impl Label for () {
     fn labeler() -> Self::Labeler {
         NoLabeling
     }
+
+    fn label() -> &'static str {
+        ""
+    }
 }
 
 impl<T: Label> Label for Option<T> {
@@ -20,6 +129,14 @@ impl<T: Label> Label for Option<T> {
     fn labeler() -> Self::Labeler {
         <T as Label>::labeler()
     }
+
+    fn label() -> &'static str {
+        <T as Label>::label()
+    }
+
+    fn prefix() -> Option<&'static str> {
+        <T as Label>::prefix()
+    }
 }
 
 impl<T: Label, E> Label for Result<T, E> {
@@ -28,15 +145,127 @@ impl<T: Label, E> Label for Result<T, E> {
     fn labeler() -> Self::Labeler {
         <T as Label>::labeler()
     }
+
+    fn label() -> &'static str {
+        <T as Label>::label()
+    }
+
+    fn prefix() -> Option<&'static str> {
+        <T as Label>::prefix()
+    }
 }
 
-impl<K: Label, V: Label> Label for HashMap<K, V> {
+macro_rules! transparent_label {
+    ($($t:ident)::+) => {
+        impl<T: Label + ?Sized> Label for $($t)::+<T> {
+            type Labeler = <T as Label>::Labeler;
+
+            fn labeler() -> Self::Labeler {
+                <T as Label>::labeler()
+            }
+
+            fn label() -> &'static str {
+                <T as Label>::label()
+            }
+
+            fn prefix() -> Option<&'static str> {
+                <T as Label>::prefix()
+            }
+        }
+    };
+}
+
+transparent_label!(Box);
+transparent_label!(Rc);
+transparent_label!(Arc);
+
+impl<B: Label + ToOwned + ?Sized> Label for Cow<'_, B> {
+    type Labeler = <B as Label>::Labeler;
+
+    fn labeler() -> Self::Labeler {
+        <B as Label>::labeler()
+    }
+
+    fn label() -> &'static str {
+        <B as Label>::label()
+    }
+
+    fn prefix() -> Option<&'static str> {
+        <B as Label>::prefix()
+    }
+}
+
+/// The label of a type parameter, where an unlabeled type renders as `()`.
+pub fn param_label<T: Label + ?Sized>() -> &'static str {
+    match <T as Label>::label() {
+        "" => "()",
+        label => label,
+    }
+}
+
+fn generic_labeling(name: &str, params: &[&str]) -> CustomLabeling {
+    CustomLabeling::from(format!("{name}<{}>", params.join(",")))
+}
+
+macro_rules! collection_label {
+    ($name:ident<$($p:ident),+> $(, $extra:ident)?) => {
+        impl<$($p: Label,)+ $($extra)?> Label for $name<$($p,)+ $($extra)?> {
+            type Labeler = CustomLabeling;
+
+            fn labeler() -> Self::Labeler {
+                generic_labeling(stringify!($name), &[$(param_label::<$p>()),+])
+            }
+        }
+    };
+}
+
+collection_label!(Vec<T>);
+collection_label!(VecDeque<T>);
+collection_label!(HashSet<T>, S);
+collection_label!(BTreeSet<T>);
+collection_label!(HashMap<K, V>, S);
+collection_label!(BTreeMap<K, V>);
+
+macro_rules! tuple_label {
+    ($($p:ident),+) => {
+        impl<$($p: Label),+> Label for ($($p,)+) {
+            type Labeler = CustomLabeling;
+
+            fn labeler() -> Self::Labeler {
+                let params = [$(param_label::<$p>()),+];
+                let trailing = if params.len() == 1 { "," } else { "" };
+                CustomLabeling::from(format!("({}{trailing})", params.join(",")))
+            }
+        }
+    };
+}
+
+tuple_label!(A);
+tuple_label!(A, B);
+tuple_label!(A, B, C);
+tuple_label!(A, B, C, D);
+tuple_label!(A, B, C, D, E);
+tuple_label!(A, B, C, D, E, F);
+tuple_label!(A, B, C, D, E, F, G);
+tuple_label!(A, B, C, D, E, F, G, H);
+tuple_label!(A, B, C, D, E, F, G, H, I);
+tuple_label!(A, B, C, D, E, F, G, H, I, J);
+tuple_label!(A, B, C, D, E, F, G, H, I, J, K);
+tuple_label!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+impl<T: Label, const N: usize> Label for [T; N] {
     type Labeler = CustomLabeling;
 
     fn labeler() -> Self::Labeler {
-        let k_label = <K as Label>::labeler().label();
-        let v_label = <V as Label>::labeler().label();
-        CustomLabeling::from(format!("HashMap<{k_label},{v_label}"))
+        CustomLabeling::from(format!("[{};{N}]", param_label::<T>()))
+    }
+}
+
+impl<T: Label> Label for [T] {
+    type Labeler = CustomLabeling;
+
+    fn labeler() -> Self::Labeler {
+        CustomLabeling::from(format!("[{}]", param_label::<T>()))
     }
 }
 
@@ -48,6 +277,10 @@ macro_rules! primitive_label {
             fn labeler() -> Self::Labeler {
                 MakeLabeling::<Self>::default()
             }
+
+            fn label() -> &'static str {
+                stringify!($i)
+            }
         }
     };
 }
@@ -57,6 +290,7 @@ primitive_label!(char);
 primitive_label!(f32);
 primitive_label!(f64);
 primitive_label!(i8);
+primitive_label!(i16);
 primitive_label!(i32);
 primitive_label!(i64);
 primitive_label!(i128);
@@ -67,12 +301,20 @@ primitive_label!(u32);
 primitive_label!(u64);
 primitive_label!(u128);
 primitive_label!(usize);
+primitive_label!(str);
 primitive_label!(String);
+primitive_label!(Duration);
+primitive_label!(Instant);
+primitive_label!(SystemTime);
 
-impl<'a> Label for &'a str {
+impl Label for &str {
     type Labeler = MakeLabeling<Self>;
 
     fn labeler() -> Self::Labeler {
         MakeLabeling::<Self>::default()
     }
+
+    fn label() -> &'static str {
+        "&str"
+    }
 }
@@ -6,6 +6,7 @@ clippy::nursery,
 future_incompatible,
 rust_2018_idioms
 )]
+#![allow(clippy::multiple_crate_versions)]
 
 #[cfg(feature = "derive")]
 #[allow(unused_imports)]
@@ -15,6 +16,15 @@ extern crate pretty_snowflake_derive;
 #[doc(hidden)]
 pub use pretty_snowflake_derive::*;
 
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "inventory")]
+    pub use inventory;
+    pub use serde;
+
+    pub use crate::label::param_label;
+}
+
 use serde::{Deserialize, Serialize};
 use validator::{Validate, ValidationErrors};
 
@@ -22,32 +32,49 @@ use validator::{Validate, ValidationErrors};
 mod tests;
 
 pub mod generator;
+pub mod id_serde;
+#[cfg(any(feature = "metrics", feature = "tracing"))]
+pub mod instrument;
 mod label;
 mod labeling;
 mod pretty;
+pub mod registry;
 mod snowflake;
 
 #[cfg(feature = "envelope")]
 pub mod envelope;
 
+#[cfg(feature = "diesel")]
+mod diesel_impl;
+#[cfg(feature = "sqlx")]
+mod sqlx_impl;
+
 use std::cmp::Ordering;
 use std::fmt;
 
-pub use label::Label;
+pub use label::{Label, RelabelFrom};
 pub use labeling::{CustomLabeling, Labeling, MakeLabeling, NoLabeling};
-pub use pretty::{Alphabet, AlphabetCodec, Codec, Id, IdPrettifier, PrettyIdGenerator};
+pub use pretty::{
+    Alphabet, AlphabetCodec, AnyId, Codec, ConfigurationError, ConversionError, Id, IdPrettifier, ObfuscationKey,
+    Obfuscator, PrettyIdGenerator, MAX_PARTS_SIZE, PREFIX_DELIMITER,
+};
 
-pub use crate::snowflake::{Generator, IdGenerator, LazyGenerator, RealTimeGenerator, SnowflakeIdGenerator};
+pub use crate::snowflake::{
+    Generator, Id as SnowflakeId, IdGenerator, LazyGenerator, RealTimeGenerator, SnowflakeIdGenerator,
+    EPOCH as SNOWFLAKE_EPOCH,
+};
 
 pub type LabeledRealtimeIdGenerator<T> = PrettyIdGenerator<T, <T as Label>::Labeler, RealTimeGenerator, AlphabetCodec>;
 pub type LabeledBasicIdGenerator<T> = PrettyIdGenerator<T, <T as Label>::Labeler, Generator, AlphabetCodec>;
 pub type LabeledLazyIdGenerator<T> = PrettyIdGenerator<T, <T as Label>::Labeler, LazyGenerator, AlphabetCodec>;
 
 /// Used to supplement the sectionalization attribute of the Snowflake algorithm in a distributed
-/// environment. The machine_id and node_id are combined to form a unique worker_id used by the
-/// Snowflake algorithm. This worker_id must be unique for a target identifier space (e.g.,
-/// identifier for a type of entity), otherwise identifier collisions can easily occur even in a
-/// light concurrent environment.
+/// environment.
+///
+/// The machine_id and node_id are combined to form a unique worker_id used by the Snowflake
+/// algorithm. This worker_id must be unique for a target identifier space (e.g., identifier for a
+/// type of entity), otherwise identifier collisions can easily occur even in a light concurrent
+/// environment.
 #[derive(Debug, Copy, Clone, Validate, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct MachineNode {
     /// For a target identifier space, the machine_id represents the largest granularity of
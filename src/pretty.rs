@@ -1,14 +1,19 @@
+pub use any_id::AnyId;
 pub use codec::{Alphabet, AlphabetCodec, Codec};
-pub use id::Id;
-pub use prettifier::IdPrettifier;
+pub use id::{Id, PREFIX_DELIMITER};
+pub use obfuscation::{ObfuscationKey, Obfuscator};
+pub use prettifier::{ConfigurationError, ConversionError, IdPrettifier, MAX_PARTS_SIZE};
+use smol_str::SmolStr;
 use std::marker::PhantomData;
 
 #[cfg(test)]
 mod tests;
 
+mod any_id;
 mod codec;
 mod damm;
 mod id;
+mod obfuscation;
 mod prettifier;
 
 use crate::{
@@ -25,6 +30,8 @@ where
     generator: SnowflakeIdGenerator<G>,
     prettifier: IdPrettifier<C>,
     labeling: L,
+    label: &'static str,
+    prefix: Option<&'static str>,
     marker: PhantomData<T>,
 }
 
@@ -77,6 +84,8 @@ where
             generator,
             prettifier,
             labeling,
+            label: T::label(),
+            prefix: T::prefix(),
             marker: PhantomData,
         }
     }
@@ -88,6 +97,8 @@ where
             generator,
             prettifier,
             labeling,
+            label: T::label(),
+            prefix: T::prefix(),
             marker: PhantomData,
         }
     }
@@ -105,26 +116,47 @@ where
 {
     pub fn single_node_labeling(labeling: L, prettifier: IdPrettifier<C>) -> Self {
         let generator = SnowflakeIdGenerator::single_node();
+        let label = crate::label::intern_custom_label(labeling.label());
         Self {
             generator,
             prettifier,
             labeling,
+            label,
+            prefix: None,
             marker: PhantomData,
         }
     }
 
     pub fn distributed_labeling(machine_node: MachineNode, labeling: L, prettifier: IdPrettifier<C>) -> Self {
         let generator = SnowflakeIdGenerator::distributed(machine_node);
+        let label = crate::label::intern_custom_label(labeling.label());
         Self {
             generator,
             prettifier,
             labeling,
+            label,
+            prefix: None,
             marker: PhantomData,
         }
     }
 
+    pub const fn labeling(&self) -> &L {
+        &self.labeling
+    }
+
+    /// Generates the next id. The label is computed and interned once, when the generator is
+    /// constructed, so generating an id does not allocate for it.
     pub fn next_id(&self) -> Id<T> {
-        Id::new(self.labeling.label(), self.generator.next_id(), &self.prettifier)
+        let snowflake = self.generator.next_labeled_id(self.label);
+        let pretty = SmolStr::new(self.prettifier.prettify(snowflake));
+        Id::from_parts(SmolStr::new_static(self.label), self.prefix, snowflake, pretty)
+    }
+
+    /// Generates the next id labeled for `U`, such as for the shared generator in the
+    /// [`generator`](crate::generator) module.
+    pub(crate) fn next_id_as<U: Label>(&self) -> Id<U> {
+        let snowflake = self.generator.next_labeled_id(<U as Label>::label());
+        Id::from_snowflake(snowflake, &self.prettifier)
     }
 }
 
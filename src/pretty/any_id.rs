@@ -0,0 +1,215 @@
+use std::any::TypeId;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+
+use crate::pretty::codec::Codec;
+use crate::pretty::id::PREFIX_DELIMITER;
+use crate::pretty::prettifier::IdPrettifier;
+use crate::registry::{self, LabelRegistration};
+use crate::snowflake::Id as SnowflakeId;
+use crate::{Id, Label};
+
+/// A type-erased pretty id, such as one parsed via
+/// [`registry::parse_any`](crate::registry::parse_any). It may be converted back into a typed
+/// `Id<T>` via [`AnyId::downcast`].
+///
+/// Unlike `Id<T>`, ids of different types may be kept together; e.g., in a `Vec<AnyId>` of touched
+/// entities. Ids are ordered by snowflake, and then by label. Ids of distinct types sharing a label
+/// are not equal. An `AnyId` serializes as its label, snowflake and prefixed pretty form, as `Id<T>`
+/// does; on deserialization, its type is recovered from the [`registry`](crate::registry) by label.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "AnyIdRep", from = "AnyIdRep")]
+pub struct AnyId {
+    label: SmolStr,
+    snowflake: SnowflakeId,
+    pretty: SmolStr,
+    registration: Option<LabelRegistration>,
+}
+
+impl AnyId {
+    pub(crate) fn from_registration<C: Codec>(
+        registration: LabelRegistration, snowflake: SnowflakeId, prettifier: &IdPrettifier<C>,
+    ) -> Self {
+        Self {
+            label: SmolStr::new_static(registration.label()),
+            snowflake,
+            pretty: SmolStr::new(prettifier.prettify(snowflake)),
+            registration: Some(registration),
+        }
+    }
+
+    #[inline]
+    pub fn label(&self) -> &str {
+        self.label.as_str()
+    }
+
+    #[inline]
+    pub fn prefix(&self) -> Option<&'static str> {
+        self.registration.and_then(|r| r.prefix())
+    }
+
+    #[inline]
+    pub fn pretty(&self) -> &str {
+        self.pretty.as_str()
+    }
+
+    #[inline]
+    pub fn num(&self) -> i64 {
+        self.snowflake.into()
+    }
+
+    /// The name of the Rust type the id belongs to, if it is known.
+    pub fn type_name(&self) -> Option<&'static str> {
+        self.registration.map(|r| r.type_name())
+    }
+
+    /// Returns true if the id belongs to `T`. An id deserialized before its type was registered is
+    /// looked up in the registry again, and an id of an unregistered type belongs to no type.
+    pub fn is<T: Label + 'static>(&self) -> bool {
+        self.registration
+            .or_else(|| registry::lookup(&self.label))
+            .is_some_and(|r| r.type_id() == TypeId::of::<T>())
+    }
+
+    /// Recovers the typed id, or returns the id unchanged if it does not belong to `T`.
+    pub fn downcast<T: Label + 'static>(self) -> Result<Id<T>, Self> {
+        if !self.is::<T>() {
+            return Err(self);
+        }
+
+        // An id deserialized before its type was registered keeps the prefixed pretty form.
+        let pretty = match self.registration {
+            Some(_) => self.pretty,
+            None => match Id::<T>::strip_prefix(&self.pretty) {
+                Ok(pretty) => SmolStr::new(pretty),
+                Err(_) => return Err(self),
+            },
+        };
+        Ok(Id::from_parts(
+            self.label,
+            <T as Label>::prefix(),
+            self.snowflake,
+            pretty,
+        ))
+    }
+
+    fn type_id(&self) -> Option<TypeId> {
+        self.registration.map(|r| r.type_id())
+    }
+
+    fn prefixed_pretty(&self) -> SmolStr {
+        self.prefix().map_or_else(
+            || self.pretty.clone(),
+            |prefix| SmolStr::new(format!("{prefix}{PREFIX_DELIMITER}{}", self.pretty)),
+        )
+    }
+}
+
+impl fmt::Debug for AnyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.debug_struct("AnyId")
+                .field("label", &self.label)
+                .field("snowflake", &self.snowflake)
+                .field("pretty", &self.pretty)
+                .field("type_name", &self.type_name())
+                .finish()
+        } else {
+            fmt::Display::fmt(self, f)
+        }
+    }
+}
+
+impl fmt::Display for AnyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(prefix) = self.prefix() {
+            write!(f, "{prefix}{PREFIX_DELIMITER}{}", self.pretty)
+        } else if self.label.is_empty() {
+            f.write_str(self.pretty.as_str())
+        } else {
+            write!(f, "{}::{}", self.label, self.pretty)
+        }
+    }
+}
+
+impl<T: Label + 'static> From<Id<T>> for AnyId {
+    fn from(id: Id<T>) -> Self {
+        let (label, snowflake, pretty) = id.into_parts();
+        Self {
+            label,
+            snowflake,
+            pretty,
+            registration: Some(LabelRegistration::new::<T>()),
+        }
+    }
+}
+
+impl PartialEq for AnyId {
+    fn eq(&self, other: &Self) -> bool {
+        self.snowflake == other.snowflake && self.label == other.label && self.type_id() == other.type_id()
+    }
+}
+
+impl Eq for AnyId {}
+
+impl Ord for AnyId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.snowflake
+            .cmp(&other.snowflake)
+            .then_with(|| self.label.cmp(&other.label))
+            .then_with(|| self.type_id().cmp(&other.type_id()))
+    }
+}
+
+impl PartialOrd for AnyId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for AnyId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.snowflake.hash(state);
+        self.label.hash(state);
+        self.type_id().hash(state);
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "AnyId")]
+struct AnyIdRep {
+    label: SmolStr,
+    snowflake: SnowflakeId,
+    pretty: SmolStr,
+}
+
+// The pretty form is serialized with the prefix of the id's type, as `Id<T>` serializes it.
+impl From<AnyId> for AnyIdRep {
+    fn from(id: AnyId) -> Self {
+        Self {
+            pretty: id.prefixed_pretty(),
+            label: id.label,
+            snowflake: id.snowflake,
+        }
+    }
+}
+
+impl From<AnyIdRep> for AnyId {
+    fn from(rep: AnyIdRep) -> Self {
+        let registration = registry::lookup(&rep.label);
+        let stripped = registration.and_then(|r| r.prefix()).and_then(|prefix| {
+            let pretty = rep.pretty.strip_prefix(prefix)?.strip_prefix(PREFIX_DELIMITER)?;
+            Some(SmolStr::new(pretty))
+        });
+        Self {
+            label: rep.label,
+            snowflake: rep.snowflake,
+            pretty: stripped.unwrap_or(rep.pretty),
+            registration,
+        }
+    }
+}
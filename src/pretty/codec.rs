@@ -1,9 +1,21 @@
 use once_cell::sync::Lazy;
 use tailcall::tailcall;
 
+use crate::pretty::prettifier::ConversionError;
+
 pub trait Codec {
     fn encode(&self, number: i64) -> String;
-    fn decode(&self, value: &str) -> i64;
+
+    /// Decodes an encoded part back into its number. Decoding must never panic on malformed input;
+    /// instead an error is returned.
+    fn decode(&self, value: &str) -> Result<i64, ConversionError>;
+
+    /// Returns true if, padded to equal length with the encoding of zero, encoded numbers compare
+    /// byte-wise in the same order as the numbers themselves. Codecs are conservatively assumed not
+    /// to preserve order.
+    fn is_order_preserving(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -38,18 +50,25 @@ impl Codec for AlphabetCodec {
         do_encode(&self.0, number, String::default())
     }
 
-    fn decode(&self, value: &str) -> i64 {
+    fn is_order_preserving(&self) -> bool {
+        self.0.is_ascending()
+    }
+
+    fn decode(&self, value: &str) -> Result<i64, ConversionError> {
         value
             .chars()
             .rev()
-            .fold(ResultWithIndex::default(), |acc, c| {
-                let encoded_part = self.0.index_of(c) as i64;
-                let base_placement = (self.0.base as i64).pow(acc.pos as u32);
-                let acc_inc = encoded_part * base_placement;
-                let new_acc = acc.result + acc_inc;
-                acc.increment_w_result(new_acc)
+            .try_fold(ResultWithIndex::default(), |acc, c| {
+                let encoded_part = self.0.index_of(c).ok_or(ConversionError::InvalidCharacter(c))? as i64;
+                let new_acc = u32::try_from(acc.pos)
+                    .ok()
+                    .and_then(|pos| (self.0.base as i64).checked_pow(pos))
+                    .and_then(|base_placement| encoded_part.checked_mul(base_placement))
+                    .and_then(|acc_inc| acc.result.checked_add(acc_inc))
+                    .ok_or_else(|| ConversionError::InvalidId(value.to_string()))?;
+                Ok(acc.increment_w_result(new_acc))
             })
-            .result
+            .map(|acc| acc.result)
     }
 }
 
@@ -87,8 +106,16 @@ impl Alphabet {
             .expect("failed on attempted out-of-bounds access.")
     }
 
-    pub fn index_of(&self, c: char) -> usize {
-        let pos = self.elements.chars().position(|a| a == c);
-        pos.expect("failed to id character in alphabet")
+    /// Returns true if the alphabet has at least two ASCII characters in strictly ascending byte
+    /// order, so that encodings sort in numeric order.
+    pub fn is_ascending(&self) -> bool {
+        let bytes = self.elements.as_bytes();
+        bytes.len() >= 2 && self.elements.is_ascii() && bytes.windows(2).all(|w| w[0] < w[1])
+    }
+
+    /// Returns the position of the character in the alphabet, or `None` if the character is not
+    /// part of it.
+    pub fn index_of(&self, c: char) -> Option<usize> {
+        self.elements.chars().position(|a| a == c)
     }
 }
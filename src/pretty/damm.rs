@@ -7,10 +7,9 @@ pub fn encode(rep: &str) -> String {
     base
 }
 
-#[allow(dead_code)]
 pub fn decode(rep: &str) -> Option<&str> {
     if is_valid(rep) {
-        rep.get(..(rep.len() - 1))
+        rep.len().checked_sub(1).and_then(|len| rep.get(..len))
     } else {
         None
     }
@@ -4,53 +4,88 @@ use serde::Deserializer;
 
 use pretty_type_name::pretty_type_name;
 use smol_str::SmolStr;
+use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
+use std::ops::{RangeBounds, RangeInclusive};
+use std::time::SystemTime;
 
 use crate::pretty::codec::Codec;
-use crate::pretty::prettifier::IdPrettifier;
+use crate::pretty::prettifier::{ConversionError, IdPrettifier};
 use crate::snowflake::Id as SnowflakeId;
-use crate::{Label, Labeling};
+use crate::{Label, RelabelFrom};
 
 const ID_SNOWFLAKE: &str = "snowflake";
 const ID_PRETTY: &str = "pretty";
 const FIELDS: [&str; 2] = [ID_SNOWFLAKE, ID_PRETTY];
 
+/// Separates the prefix of a type's ids from the pretty form; e.g., `ord_HPJD-72036-HAPK-58077`.
+pub const PREFIX_DELIMITER: char = '_';
+
 pub struct Id<T: ?Sized> {
     label: SmolStr,
+    prefix: Option<&'static str>,
     snowflake: SnowflakeId,
     pretty: SmolStr, // todo: convert into [char; N] form to support Cpy semantics
     marker: PhantomData<T>,
 }
 
 impl<T: ?Sized> Id<T> {
+    /// Creates an id with the given label and no prefix. Prefer [`Id::from_snowflake`] for ids
+    /// labeled for `T`, which carry the prefix `T` supplies.
     pub fn new<C: Codec>(
         label: impl AsRef<str>, snowflake: impl Into<SnowflakeId>, prettifier: &IdPrettifier<C>,
     ) -> Self {
         let snowflake: SnowflakeId = snowflake.into();
         Self {
             label: SmolStr::new(label.as_ref()),
+            prefix: None,
             snowflake,
-            pretty: SmolStr::new_inline(&prettifier.prettify(snowflake)),
+            pretty: SmolStr::new(prettifier.prettify(snowflake)),
             marker: PhantomData,
         }
     }
 
+    /// Creates an id from its parts, with no prefix.
     pub fn direct(label: impl AsRef<str>, snowflake: impl Into<SnowflakeId>, pretty: impl AsRef<str>) -> Self {
         Self {
             label: SmolStr::new(label.as_ref()),
+            prefix: None,
             snowflake: snowflake.into(),
-            pretty: SmolStr::new_inline(pretty.as_ref()),
+            pretty: SmolStr::new(pretty.as_ref()),
             marker: PhantomData,
         }
     }
 
-    pub fn relabel<B: Label>(&self) -> Id<B> {
-        let b_labeler = B::labeler();
+    pub(crate) const fn from_parts(
+        label: SmolStr, prefix: Option<&'static str>, snowflake: SnowflakeId, pretty: SmolStr,
+    ) -> Self {
+        Self {
+            label,
+            prefix,
+            snowflake,
+            pretty,
+            marker: PhantomData,
+        }
+    }
+
+    pub(crate) fn into_parts(self) -> (SmolStr, SnowflakeId, SmolStr) {
+        (self.label, self.snowflake, self.pretty)
+    }
+
+    /// Converts the id into an id of `B`, which must declare the conversion via [`RelabelFrom`].
+    pub fn relabel<B: Label + RelabelFrom<T>>(&self) -> Id<B> {
+        self.unchecked_relabel()
+    }
+
+    /// Converts the id into an id of any labeled type. This bypasses the type safety provided by the
+    /// id's type parameter, so prefer [`Id::relabel`] where the conversion can be declared.
+    pub fn unchecked_relabel<B: Label>(&self) -> Id<B> {
         Id {
-            label: SmolStr::new(b_labeler.label().as_ref()),
+            label: SmolStr::new_static(B::label()),
+            prefix: B::prefix(),
             snowflake: self.snowflake,
             pretty: self.pretty.clone(),
             marker: PhantomData,
@@ -62,19 +97,43 @@ impl<T: ?Sized> Id<T> {
         self.label.as_str()
     }
 
+    /// The short code prefixing the string form of the id, if its label supplies one.
+    #[inline]
+    pub const fn prefix(&self) -> Option<&'static str> {
+        self.prefix
+    }
+
     #[inline]
     pub fn pretty(&self) -> &str {
         self.pretty.as_str()
     }
 
+    /// The pretty form of the id, preceded by its prefix if it has one; e.g.,
+    /// `ord_HPJD-72036-HAPK-58077`. This is the form used by `String::from` and serialization.
+    pub fn prefixed_pretty(&self) -> Cow<'_, str> {
+        #[allow(clippy::option_if_let_else)]
+        match self.prefix {
+            None => Cow::Borrowed(self.pretty.as_str()),
+            Some(prefix) => Cow::Owned(format!("{prefix}{PREFIX_DELIMITER}{}", self.pretty)),
+        }
+    }
+
     #[inline]
     pub fn num(&self) -> i64 {
         self.snowflake.into()
     }
 
+    #[cfg(any(feature = "sqlx", feature = "diesel"))]
+    #[inline]
+    pub(crate) const fn snowflake(&self) -> &SnowflakeId {
+        &self.snowflake
+    }
+
     #[inline]
     pub fn write_pretty_label(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if self.label.is_empty() {
+        if let Some(prefix) = self.prefix {
+            f.write_fmt(format_args!("{prefix}{PREFIX_DELIMITER}{}", self.pretty))
+        } else if self.label.is_empty() {
             f.write_str(self.pretty.as_str())
         } else {
             f.write_fmt(format_args!("{}::{}", self.label, self.pretty))
@@ -82,6 +141,60 @@ impl<T: ?Sized> Id<T> {
     }
 }
 
+impl<T: Label + ?Sized> Id<T> {
+    /// Parses an id from its pretty form, as produced by `String::from(id)`, or from its labeled
+    /// form, as produced by `Display`. A label that does not match the label of `T` is rejected.
+    ///
+    /// If `T` supplies a prefix, both forms are the prefixed form, and the prefix is required.
+    pub fn parse_with<C: Codec>(rep: &str, prettifier: &IdPrettifier<C>) -> Result<Self, ConversionError> {
+        let pretty = if <T as Label>::prefix().is_some() {
+            Self::strip_prefix(rep)?
+        } else {
+            match rep.rsplit_once("::") {
+                None => rep,
+                Some((label, pretty)) if label == <T as Label>::label() => pretty,
+                Some(_) => return Err(ConversionError::InvalidId(rep.to_string())),
+            }
+        };
+
+        let snowflake = prettifier.to_id_seed(pretty)?;
+        Ok(Self::from_snowflake(snowflake, prettifier))
+    }
+
+    /// Removes the prefix of `T`, if `T` supplies one, from the prefixed pretty form of an id. An
+    /// id lacking the prefix, such as the id of another type, is rejected.
+    pub fn strip_prefix(rep: &str) -> Result<&str, ConversionError> {
+        #[allow(clippy::option_if_let_else)]
+        match <T as Label>::prefix() {
+            None => Ok(rep),
+            Some(prefix) => rep
+                .strip_prefix(prefix)
+                .and_then(|r| r.strip_prefix(PREFIX_DELIMITER))
+                .ok_or_else(|| ConversionError::InvalidPrefix { expected: prefix, id: rep.to_string() }),
+        }
+    }
+
+    /// Creates an id labeled for `T` from its snowflake number, without allocating for the label.
+    pub fn from_snowflake<C: Codec>(snowflake: impl Into<SnowflakeId>, prettifier: &IdPrettifier<C>) -> Self {
+        let snowflake: SnowflakeId = snowflake.into();
+        Self::from_parts(
+            SmolStr::new_static(<T as Label>::label()),
+            <T as Label>::prefix(),
+            snowflake,
+            SmolStr::new(prettifier.prettify(snowflake)),
+        )
+    }
+
+    /// The bounds of the ids of `T` that may be generated within a time range. See
+    /// [`SnowflakeId::range_for`].
+    pub fn range_for<Ts: Copy + Into<SystemTime>, C: Codec>(
+        range: impl RangeBounds<Ts>, prettifier: &IdPrettifier<C>,
+    ) -> RangeInclusive<Self> {
+        let (start, end) = SnowflakeId::range_for(range).into_inner();
+        Self::from_snowflake(start, prettifier)..=Self::from_snowflake(end, prettifier)
+    }
+}
+
 #[allow(unsafe_code)]
 unsafe impl<T: ?Sized> Send for Id<T> {}
 #[allow(unsafe_code)]
@@ -91,6 +204,7 @@ impl<T: ?Sized> Clone for Id<T> {
     fn clone(&self) -> Self {
         Self {
             label: self.label.clone(),
+            prefix: self.prefix,
             snowflake: self.snowflake,
             pretty: self.pretty.clone(),
             marker: PhantomData,
@@ -136,7 +250,7 @@ impl<T: ?Sized> From<Id<T>> for i64 {
 
 impl<T: ?Sized> From<Id<T>> for String {
     fn from(id: Id<T>) -> Self {
-        id.pretty.to_string()
+        id.prefixed_pretty().into_owned()
     }
 }
 
@@ -173,7 +287,7 @@ impl<T: ?Sized> Serialize for Id<T> {
     {
         let mut state = serializer.serialize_struct("Id", 2)?;
         state.serialize_field(ID_SNOWFLAKE, &self.snowflake)?;
-        state.serialize_field(ID_PRETTY, &self.pretty)?;
+        state.serialize_field(ID_PRETTY, &self.prefixed_pretty())?;
         state.end()
     }
 }
@@ -241,9 +355,14 @@ impl<'de, T: Label + ?Sized> Deserialize<'de> for Id<T> {
             {
                 let snowflake: i64 = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(0, &self))?;
                 let pretty: String = seq.next_element()?.ok_or_else(|| de::Error::invalid_length(1, &self))?;
-                let labeler = <T as Label>::labeler();
-                let label = labeler.label();
-                Ok(Id::direct(label, snowflake, pretty))
+                let pretty = Id::<T>::strip_prefix(&pretty).map_err(de::Error::custom)?;
+                let label = SmolStr::new_static(<T as Label>::label());
+                Ok(Id::from_parts(
+                    label,
+                    <T as Label>::prefix(),
+                    snowflake.into(),
+                    SmolStr::new(pretty),
+                ))
             }
 
             fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
@@ -271,9 +390,14 @@ impl<'de, T: Label + ?Sized> Deserialize<'de> for Id<T> {
 
                 let snowflake: i64 = snowflake.ok_or_else(|| de::Error::missing_field(ID_SNOWFLAKE))?;
                 let pretty: String = pretty.ok_or_else(|| de::Error::missing_field(ID_PRETTY))?;
-                let labeler = <T as Label>::labeler();
-                let label = labeler.label();
-                Ok(Id::direct(label, snowflake, pretty))
+                let pretty = Id::<T>::strip_prefix(&pretty).map_err(de::Error::custom)?;
+                let label = SmolStr::new_static(<T as Label>::label());
+                Ok(Id::from_parts(
+                    label,
+                    <T as Label>::prefix(),
+                    snowflake.into(),
+                    SmolStr::new(pretty),
+                ))
             }
         }
 
@@ -0,0 +1,142 @@
+use std::fmt;
+
+const ROUNDS: usize = 8;
+const SIGN_BIT: u64 = 1 << 63;
+
+/// A secret key used to obfuscate ids, identified by an id that is embedded in obfuscated pretty
+/// ids so that ids remain reversible after the key is rotated.
+#[derive(Clone)]
+pub struct ObfuscationKey {
+    id: u16,
+    round_keys: [u64; ROUNDS],
+}
+
+impl ObfuscationKey {
+    /// Derives the round keys of the permutation from a 128-bit secret.
+    pub fn new(id: u16, secret: u128) -> Self {
+        let (low, high) = (secret as u64, (secret >> 64) as u64);
+        let mut state = low;
+        let mut round_keys = [0; ROUNDS];
+        for round_key in &mut round_keys {
+            state = splitmix64(state ^ high);
+            *round_key = state;
+        }
+
+        Self { id, round_keys }
+    }
+
+    pub const fn id(&self) -> u16 {
+        self.id
+    }
+
+    fn permute(&self, value: u64) -> u64 {
+        let (mut left, mut right) = ((value >> 32) as u32, value as u32);
+        for round_key in &self.round_keys {
+            let next = left ^ round(right, *round_key);
+            left = right;
+            right = next;
+        }
+
+        (u64::from(left) << 32) | u64::from(right)
+    }
+
+    fn unpermute(&self, value: u64) -> u64 {
+        let (mut left, mut right) = ((value >> 32) as u32, value as u32);
+        for round_key in self.round_keys.iter().rev() {
+            let previous = right ^ round(left, *round_key);
+            right = left;
+            left = previous;
+        }
+
+        (u64::from(left) << 32) | u64::from(right)
+    }
+
+    /// Applies the keyed permutation, cycle-walking so that the sign of the value is preserved.
+    /// Non-negative seeds thus remain non-negative.
+    fn obfuscate(&self, seed: i64) -> i64 {
+        let sign = seed as u64 & SIGN_BIT;
+        let mut value = self.permute(seed as u64);
+        while value & SIGN_BIT != sign {
+            value = self.permute(value);
+        }
+        value as i64
+    }
+
+    fn reveal(&self, obfuscated: i64) -> i64 {
+        let sign = obfuscated as u64 & SIGN_BIT;
+        let mut value = self.unpermute(obfuscated as u64);
+        while value & SIGN_BIT != sign {
+            value = self.unpermute(value);
+        }
+        value as i64
+    }
+}
+
+impl fmt::Debug for ObfuscationKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ObfuscationKey")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
+
+const fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn round(half: u32, round_key: u64) -> u32 {
+    (splitmix64(u64::from(half) ^ round_key) >> 32) as u32
+}
+
+/// Hides the creation time and volume otherwise revealed by snowflake ids, via a keyed, reversible
+/// permutation (a Feistel network) applied to ids before they are prettified.
+///
+/// New ids are obfuscated with the current key. Retired keys are kept so that ids obfuscated
+/// before a key rotation can still be recovered via `IdPrettifier::to_id_seed`.
+///
+/// This is obfuscation rather than encryption: the permutation is not a vetted cipher, so it
+/// should not protect ids whose secrecy is critical.
+#[derive(Debug, Clone)]
+pub struct Obfuscator {
+    current: ObfuscationKey,
+    retired: Vec<ObfuscationKey>,
+}
+
+impl Obfuscator {
+    pub const fn new(key: ObfuscationKey) -> Self {
+        Self { current: key, retired: Vec::new() }
+    }
+
+    /// Keeps accepting ids obfuscated with a previously retired key.
+    pub fn with_retired_key(mut self, key: ObfuscationKey) -> Self {
+        self.retired.push(key);
+        self
+    }
+
+    /// Obfuscates new ids with the given key, retiring the current one.
+    pub fn rotate(mut self, key: ObfuscationKey) -> Self {
+        let retired = std::mem::replace(&mut self.current, key);
+        self.retired.push(retired);
+        self
+    }
+
+    pub const fn current_key_id(&self) -> u16 {
+        self.current.id
+    }
+
+    /// Obfuscates the seed, returning the id of the key used along with the obfuscated value.
+    pub fn obfuscate(&self, seed: i64) -> (u16, i64) {
+        (self.current.id, self.current.obfuscate(seed))
+    }
+
+    /// Recovers the seed obfuscated with the identified key, or `None` if the key is unknown.
+    pub fn reveal(&self, key_id: u16, obfuscated: i64) -> Option<i64> {
+        std::iter::once(&self.current)
+            .chain(&self.retired)
+            .find(|key| key.id == key_id)
+            .map(|key| key.reveal(obfuscated))
+    }
+}
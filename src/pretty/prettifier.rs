@@ -5,6 +5,7 @@ use thiserror::Error;
 
 use super::damm;
 use crate::pretty::codec::Codec;
+use crate::pretty::obfuscation::Obfuscator;
 use crate::snowflake::Id as SnowflakeId;
 
 #[derive(Debug, Error)]
@@ -12,11 +13,55 @@ pub enum ConversionError {
     #[error("Not a valid ID: {0}")]
     InvalidId(String),
 
+    #[error("Character {0:?} is not in the codec alphabet")]
+    InvalidCharacter(char),
+
+    #[error("Unknown obfuscation key: {0}")]
+    UnknownObfuscationKey(i64),
+
+    #[error("Expected id prefix {expected:?} in {id:?}")]
+    InvalidPrefix { expected: &'static str, id: String },
+
+    #[error("No type is registered for the label of id: {0}")]
+    UnknownLabel(String),
+
     #[error("{0}")]
     ParseIntError(#[from] std::num::ParseIntError),
 }
 
+/// A prettifier configuration that cannot guarantee the properties asked of it.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum ConfigurationError {
+    #[error("Codec does not preserve numeric order in byte-wise comparison")]
+    CodecNotOrderPreserving,
+
+    #[error("Parts size must be between 1 and {MAX_PARTS_SIZE}, but was {0}")]
+    InvalidPartsSize(usize),
+
+    #[error("Delimiter must not be empty")]
+    EmptyDelimiter,
+
+    #[error("Leading zeros are required for fixed-length ids")]
+    LeadingZerosRequired,
+
+    #[error("Zero character {0:?} is not the codec's encoding of zero")]
+    InvalidZeroChar(char),
+
+    #[error("Max encoder length {actual} does not match the codec's encoding length {expected}")]
+    InvalidMaxEncoderLength { expected: usize, actual: usize },
+
+    #[error("Obfuscated ids do not preserve snowflake order")]
+    Obfuscated,
+}
+
+/// The largest parts size for which a part's numeric range fits in an `i64`.
+pub const MAX_PARTS_SIZE: usize = 18;
+
+/// The number of digits of an obfuscation key id, as covered by the check digit of an obfuscated id.
+const KEY_DIGITS: usize = 5;
+
 /// It makes Long ids more readable and user friendly, it also adds checksum.
+///
 /// Params:
 /// encoder – it the result needs to be monotonic, use monotonic Coded e.g. AlphabetCoded with
 ///     alphabet where char values are monotonic
@@ -24,6 +69,8 @@ pub enum ConversionError {
 ///     are encoded with codec)
 /// delimiter – sign between parts
 /// leadingZeros – prettifier will make id with constant length
+/// obfuscator – if set, ids are obfuscated before prettifying, and the id of the obfuscation key
+///     is prepended as an encoded part
 #[derive(Debug, Clone)]
 pub struct IdPrettifier<C: Codec> {
     pub encoder: C,
@@ -32,6 +79,7 @@ pub struct IdPrettifier<C: Codec> {
     pub leading_zeros: bool,
     pub zero_char: char,
     pub max_encoder_length: usize,
+    pub obfuscator: Option<Obfuscator>,
 }
 
 impl<C: Codec + Default> Default for IdPrettifier<C> {
@@ -51,23 +99,168 @@ impl<C: Codec + Default> Default for IdPrettifier<C> {
             leading_zeros: true,
             zero_char,
             max_encoder_length,
+            obfuscator: None,
         }
     }
 }
 
 impl<C: Codec> IdPrettifier<C> {
+    /// Creates a prettifier whose pretty ids compare byte-wise in the same order as their
+    /// (non-negative) snowflakes, so they may serve as sortable text keys; e.g., in a key-value
+    /// store. The configuration is checked via [`IdPrettifier::check_lexicographic`].
+    pub fn lexicographic(
+        encoder: C, parts_size: usize, delimiter: impl Into<String>,
+    ) -> Result<Self, ConfigurationError> {
+        if parts_size == 0 || MAX_PARTS_SIZE < parts_size {
+            return Err(ConfigurationError::InvalidPartsSize(parts_size));
+        }
+
+        let zero_char = encoder
+            .encode(0)
+            .chars()
+            .next()
+            .ok_or(ConfigurationError::CodecNotOrderPreserving)?;
+        let max_encoder_length = Self::encoded_part_length(&encoder, parts_size);
+        let prettifier = Self {
+            encoder,
+            parts_size,
+            delimiter: delimiter.into(),
+            leading_zeros: true,
+            zero_char,
+            max_encoder_length,
+            obfuscator: None,
+        };
+
+        prettifier.check_lexicographic()?;
+        Ok(prettifier)
+    }
+
+    /// Checks that pretty ids compare byte-wise in the same order as their non-negative snowflakes.
+    ///
+    /// This holds when every id has the same length, with each part padded using the codec's
+    /// encoding of zero, and when the codec preserves numeric order. A non-empty delimiter is also
+    /// required for ids to be parsed back.
+    pub fn check_lexicographic(&self) -> Result<(), ConfigurationError> {
+        if self.parts_size == 0 || MAX_PARTS_SIZE < self.parts_size {
+            return Err(ConfigurationError::InvalidPartsSize(self.parts_size));
+        }
+
+        if !self.leading_zeros {
+            return Err(ConfigurationError::LeadingZerosRequired);
+        }
+
+        if self.obfuscator.is_some() {
+            return Err(ConfigurationError::Obfuscated);
+        }
+
+        if self.delimiter.is_empty() {
+            return Err(ConfigurationError::EmptyDelimiter);
+        }
+
+        if !self.encoder.is_order_preserving() {
+            return Err(ConfigurationError::CodecNotOrderPreserving);
+        }
+
+        if !self.encoder.encode(0).starts_with(self.zero_char) {
+            return Err(ConfigurationError::InvalidZeroChar(self.zero_char));
+        }
+
+        let expected = Self::encoded_part_length(&self.encoder, self.parts_size);
+        if self.max_encoder_length != expected {
+            return Err(ConfigurationError::InvalidMaxEncoderLength { expected, actual: self.max_encoder_length });
+        }
+
+        Ok(())
+    }
+
+    fn encoded_part_length(encoder: &C, parts_size: usize) -> usize {
+        encoder.encode(10_i64.pow(parts_size as u32) - 1_i64).len()
+    }
+
     pub fn prettify(&self, id_seed: impl Into<SnowflakeId>) -> String {
-        let parts = self.divide(damm::encode(format!("{}", id_seed.into()).as_str()));
+        #[cfg(any(feature = "metrics", feature = "tracing"))]
+        let start = std::time::Instant::now();
+
+        let seed: i64 = id_seed.into().into();
+        #[allow(clippy::option_if_let_else)]
+        let pretty = match &self.obfuscator {
+            None => self.prettify_seed(seed),
+            Some(obfuscator) => {
+                let (key_id, obfuscated) = obfuscator.obfuscate(seed);
+                let key_part = self.encoder.encode(i64::from(key_id));
+                // The check digit covers the key id as well, so that a typo in the key part is
+                // detected rather than revealing the id under another key.
+                let with_key = damm::encode(&format!("{obfuscated}{key_id:0KEY_DIGITS$}"));
+                let check_digit = &with_key[with_key.len() - 1..];
+                let checked = format!("{obfuscated}{check_digit}");
+                format!("{key_part}{}{}", self.delimiter, self.prettify_checked(checked))
+            },
+        };
+
+        #[cfg(any(feature = "metrics", feature = "tracing"))]
+        crate::instrument::record_prettify(start.elapsed());
+
+        pretty
+    }
+
+    fn prettify_seed(&self, seed: i64) -> String {
+        self.prettify_checked(damm::encode(format!("{seed}").as_str()))
+    }
+
+    fn prettify_checked(&self, seed_with_check_digit: String) -> String {
+        let parts = self.divide(seed_with_check_digit);
         let parts_to_convert = self.convert_with_leading_zeros(parts, |item| self.add_leading_zeros_parts(item));
         self.convert_parts(parts_to_convert)
     }
 
+    /// Checks the pretty id's checksum. Never panics; malformed input is simply not valid.
     pub fn is_valid(&self, id: &str) -> bool {
-        damm::is_valid(self.decode_seed_with_check_digit(id).as_str())
+        match &self.obfuscator {
+            None => self
+                .decode_seed_with_check_digit(id)
+                .is_ok_and(|decoded| damm::decode(decoded.as_str()).is_some()),
+            Some(_) => self.decode_obfuscated(id).is_ok(),
+        }
     }
 
+    /// Recovers the snowflake id seed from its pretty form. This function is safe to use on
+    /// untrusted input: it never panics, and any malformed representation results in a
+    /// `ConversionError`.
     pub fn to_id_seed(&self, id: &str) -> Result<SnowflakeId, ConversionError> {
-        self.convert_to_id(id)
+        match &self.obfuscator {
+            None => self.convert_to_id(id),
+            Some(obfuscator) => {
+                let (key_id, obfuscated) = self.decode_obfuscated(id)?;
+                obfuscator
+                    .reveal(key_id, obfuscated)
+                    .map(SnowflakeId::from)
+                    .ok_or_else(|| ConversionError::UnknownObfuscationKey(i64::from(key_id)))
+            },
+        }
+    }
+
+    /// Splits an obfuscated pretty id into the id of its key and its obfuscated seed, checking the
+    /// check digit covering both.
+    fn decode_obfuscated(&self, id: &str) -> Result<(u16, i64), ConversionError> {
+        let (key_part, rep) = id
+            .split_once(self.delimiter.as_str())
+            .ok_or_else(|| ConversionError::InvalidId(id.to_string()))?;
+        let key_id = self.encoder.decode(key_part)?;
+        let key_id = u16::try_from(key_id).map_err(|_| ConversionError::UnknownObfuscationKey(key_id))?;
+
+        // The key id is checked as if it preceded the check digit, since the decoded seed may have
+        // leading zeros.
+        let decoded = self.decode_seed_with_check_digit(rep)?;
+        let at = decoded.len().saturating_sub(1);
+        let obfuscated = decoded
+            .get(..at)
+            .zip(decoded.get(at..))
+            .filter(|(obfuscated, check_digit)| {
+                damm::is_valid(&format!("{obfuscated}{key_id:0KEY_DIGITS$}{check_digit}"))
+            })
+            .map(|(obfuscated, _)| obfuscated)
+            .ok_or_else(|| ConversionError::InvalidId(id.to_string()))?;
+        Ok((key_id, i64::from_str(obfuscated)?))
     }
 
     fn divide(&self, rep: String) -> Vec<String> {
@@ -94,15 +287,10 @@ impl<C: Codec> IdPrettifier<C> {
     }
 
     fn convert_to_id(&self, rep: &str) -> Result<SnowflakeId, ConversionError> {
-        let decoded_with_check_digit = self.decode_seed_with_check_digit(rep);
-        if damm::is_valid(&decoded_with_check_digit) {
-            decoded_with_check_digit
-                .get(..(decoded_with_check_digit.len() - 1))
-                .ok_or_else(|| ConversionError::InvalidId(rep.to_string()))
-                .and_then(|decoded| SnowflakeId::from_str(decoded).map_err(|err| err.into()))
-        } else {
-            Err(ConversionError::InvalidId(rep.to_string()))
-        }
+        let decoded_with_check_digit = self.decode_seed_with_check_digit(rep)?;
+        damm::decode(&decoded_with_check_digit)
+            .ok_or_else(|| ConversionError::InvalidId(rep.to_string()))
+            .and_then(|decoded| SnowflakeId::from_str(decoded).map_err(|err| err.into()))
     }
 
     fn convert_with_leading_zeros<T, F>(&self, item: T, mut for_leading_zeros: F) -> T
@@ -116,6 +304,8 @@ impl<C: Codec> IdPrettifier<C> {
         }
     }
 
+    // `usize::is_multiple_of` is only stable since Rust 1.87.
+    #[allow(clippy::manual_is_multiple_of)]
     fn convert_parts(&self, parts: Vec<String>) -> String {
         let encode_odd = parts.len() % 2 == 0;
         let padded_converted_parts = parts.into_iter().fold(Vec::<String>::new(), |mut acc, part| {
@@ -153,28 +343,35 @@ impl<C: Codec> IdPrettifier<C> {
         lead_padded
     }
 
-    fn decode_seed_with_check_digit(&self, rep: &str) -> String {
+    #[allow(clippy::manual_is_multiple_of)]
+    fn decode_seed_with_check_digit(&self, rep: &str) -> Result<String, ConversionError> {
         let parts: Vec<&str> = rep.split(&self.delimiter).collect();
         let decode_even = parts.len() % 2 != 0;
-        let decoded_with_check_digit = parts.into_iter().fold(Vec::<String>::new(), |mut acc, part| {
+        let decoded_with_check_digit = parts.into_iter().try_fold(Vec::<String>::new(), |mut acc, part| {
             let is_even = acc.len() % 2 == 0;
             let decode_part = if decode_even { is_even } else { !is_even };
             if decode_part {
                 acc.push(part.to_string());
             } else {
-                let encoded_part = format!("{}", self.encoder.decode(part));
+                let encoded_part = format!("{}", self.encoder.decode(part)?);
                 let decoded = Self::add_leading_zeros(encoded_part, '0', self.parts_size);
                 acc.push(decoded);
             }
-            acc
-        });
+            Ok::<_, ConversionError>(acc)
+        })?;
 
         let formatted = decoded_with_check_digit.into_iter().format_with("", |ps, f| f(&ps));
-        format!("{}", formatted)
+        Ok(format!("{}", formatted))
     }
 }
 
 #[cfg(test)]
+#[allow(
+    clippy::borrow_interior_mutable_const,
+    clippy::declare_interior_mutable_const,
+    clippy::redundant_clone,
+    clippy::redundant_static_lifetimes
+)]
 mod tests {
     use once_cell::sync::Lazy;
     use pretty_assertions::assert_eq;
@@ -1,3 +1,11 @@
+#![allow(
+    clippy::borrow_interior_mutable_const,
+    clippy::declare_interior_mutable_const,
+    clippy::iter_cloned_collect,
+    clippy::redundant_closure,
+    clippy::useless_conversion
+)]
+
 use pretty_assertions::assert_eq;
 
 use crate::{AlphabetCodec, CustomLabeling, IdPrettifier, Label, LazyGenerator, PrettyIdGenerator};
@@ -32,11 +40,12 @@ fn test_labeled_generator() {
 }
 
 mod codec {
+    use claim::*;
     use once_cell::sync::Lazy;
     use pretty_assertions::assert_eq;
 
     use crate::pretty::Codec;
-    use crate::AlphabetCodec;
+    use crate::{Alphabet, AlphabetCodec};
 
     static CODEC: Lazy<AlphabetCodec> = Lazy::new(|| AlphabetCodec::default());
 
@@ -49,13 +58,32 @@ mod codec {
 
     #[test]
     fn test_decode_value() {
-        assert_eq!(CODEC.decode("BA"), 23);
-        assert_eq!(CODEC.decode("ABA"), 23);
-        assert_eq!(CODEC.decode("BAA"), 529);
-        assert_eq!(CODEC.decode("BAB"), 530);
-        assert_eq!(CODEC.decode("BAAA"), 12167);
-        assert_eq!(CODEC.decode("HAPK"), 85477);
-        assert_eq!(CODEC.decode("HPJD"), 92233);
+        assert_eq!(assert_ok!(CODEC.decode("BA")), 23);
+        assert_eq!(assert_ok!(CODEC.decode("ABA")), 23);
+        assert_eq!(assert_ok!(CODEC.decode("BAA")), 529);
+        assert_eq!(assert_ok!(CODEC.decode("BAB")), 530);
+        assert_eq!(assert_ok!(CODEC.decode("BAAA")), 12167);
+        assert_eq!(assert_ok!(CODEC.decode("HAPK")), 85477);
+        assert_eq!(assert_ok!(CODEC.decode("HPJD")), 92233);
+    }
+
+    #[test]
+    fn test_alphabet_index_of() {
+        let alphabet = Alphabet::new("ABC");
+        assert_eq!(alphabet.index_of('A'), Some(0));
+        assert_eq!(alphabet.index_of('C'), Some(2));
+        assert_eq!(alphabet.index_of('I'), None);
+        assert_eq!(alphabet.index_of('\u{0}'), None);
+    }
+
+    #[test]
+    fn test_decode_rejects_malformed_value() {
+        // regression: characters outside of the alphabet used to panic
+        assert_err!(CODEC.decode("I"));
+        assert_err!(CODEC.decode("AB-A"));
+        // regression: overlong values used to overflow
+        assert_err!(CODEC.decode("ZZZZZZZZZZZZZZZ"));
+        assert_err!(CODEC.decode(&"A".repeat(40)));
     }
 }
 
@@ -74,6 +102,13 @@ mod damm {
         assert_eq!(damm::is_valid(&with_checksum), true);
     }
 
+    #[test]
+    fn test_decode_empty() {
+        // regression: an empty representation has a valid checksum but no check digit
+        assert_eq!(damm::decode(""), None);
+        assert_eq!(damm::decode("5724"), Some("572"));
+    }
+
     #[test]
     fn test_fail_on_checking_check_digit() {
         let with_checksum = damm::encode(&format!("{}", i64::MAX));
@@ -97,8 +132,10 @@ mod damm {
 
 mod id {
     use pretty_assertions::assert_eq;
+    use serde_test::{assert_de_tokens, Token};
+    use static_assertions::{assert_impl_all, assert_not_impl_any};
 
-    use crate::{AlphabetCodec, Id, IdPrettifier, Label, LabeledRealtimeIdGenerator, MakeLabeling};
+    use crate::{AlphabetCodec, Id, IdPrettifier, Label, LabeledRealtimeIdGenerator, MakeLabeling, RelabelFrom};
 
     struct Foo;
     impl Label for Foo {
@@ -164,10 +201,72 @@ mod id {
         let before = format!("{:?}", a);
         assert_eq!(format!("String::{}", a.pretty()), before);
 
-        let b: Id<usize> = a.relabel();
+        let b: Id<usize> = a.unchecked_relabel();
         let after = format!("{:?}", b);
         assert_eq!(format!("usize::{}", b.pretty()), after);
     }
+
+    struct FooSummary;
+    impl Label for FooSummary {
+        type Labeler = MakeLabeling<Self>;
+
+        fn labeler() -> Self::Labeler {
+            MakeLabeling::default()
+        }
+    }
+
+    impl RelabelFrom<Foo> for FooSummary {}
+
+    #[test]
+    fn test_checked_relabel() {
+        assert_impl_all!(FooSummary: RelabelFrom<Foo>, RelabelFrom<FooSummary>);
+        assert_impl_all!(Option<Foo>: RelabelFrom<Foo>);
+        assert_impl_all!(Foo: RelabelFrom<Option<Foo>>, RelabelFrom<Result<Foo, String>>, RelabelFrom<Box<Foo>>);
+        assert_not_impl_any!(Foo: RelabelFrom<FooSummary>, RelabelFrom<String>);
+        assert_not_impl_any!(usize: RelabelFrom<String>);
+
+        let generator = make_generator();
+        let a: Id<Foo> = generator.next_id();
+        let b: Id<FooSummary> = a.relabel();
+        assert_eq!(a.num(), b.num());
+        assert_eq!(b.label(), "FooSummary");
+
+        let c: Id<Option<Foo>> = a.relabel();
+        assert_eq!(c.label(), "Foo");
+    }
+
+    #[test]
+    fn test_parse_with() {
+        let prettifier = IdPrettifier::<AlphabetCodec>::default();
+        let actual: Id<Foo> = Id::parse_with("ARPJ-27036-GVQS-07849", &prettifier).unwrap();
+        assert_eq!(actual.num(), 824227036833910784);
+        assert_eq!(actual.label(), "Foo");
+
+        let labeled: Id<Foo> = Id::parse_with("Foo::ARPJ-27036-GVQS-07849", &prettifier).unwrap();
+        assert_eq!(labeled, actual);
+
+        assert!(Id::<Foo>::parse_with("Bar::ARPJ-27036-GVQS-07849", &prettifier).is_err());
+        assert!(Id::<Foo>::parse_with("Foo::ARPJ-27036-GVQS-07840", &prettifier).is_err());
+    }
+
+    #[test]
+    fn test_deserialize_oversized_pretty() {
+        // regression: pretty reps longer than the inline capacity used to panic on deserialization
+        let pretty = "ARPJ-27036-GVQS-07849-ARPJ-27036-GVQS-07849";
+        let expected: Id<Foo> = Id::direct("Foo", 824227036833910784, pretty);
+        assert_de_tokens(
+            &expected,
+            &[
+                Token::Struct { name: "Id", len: 2 },
+                Token::Str("snowflake"),
+                Token::I64(824227036833910784),
+                Token::Str("pretty"),
+                Token::Str(pretty),
+                Token::StructEnd,
+            ],
+        );
+        assert_eq!(expected.pretty(), pretty);
+    }
 }
 
 mod prettifier {
@@ -270,6 +369,29 @@ mod prettifier {
         assert!(!prettifier.is_valid("ARPJ-27036-GVQZ-07489"));
     }
 
+    #[test]
+    fn test_reject_malformed_pretty_ids() {
+        let prettifier = IdPrettifier::<AlphabetCodec>::default();
+
+        // regressions for inputs that used to panic rather than return an error
+        for malformed in [
+            "",
+            "ARPJ-27036-GIQS-07849",
+            "ÄRPJ-27036-GVQS-07849",
+            "ZZZZZZZZZZZZZZZZZZZZ-0",
+            "99999999999999999999999999999999999990",
+        ] {
+            assert_err!(prettifier.to_id_seed(malformed), "input: {malformed:?}");
+            assert!(!prettifier.is_valid(malformed), "input: {malformed:?}");
+        }
+
+        // degenerate but well-formed inputs decode leniently; they must simply not panic
+        for degenerate in ["-", "--", "0", "ARPJ-27036-GVQS-07849-"] {
+            let _ = prettifier.to_id_seed(degenerate);
+            let _ = prettifier.is_valid(degenerate);
+        }
+    }
+
     #[test]
     fn test_preserve_id_monotonicity() {
         let real_time = SnowflakeIdGenerator::<RealTimeGenerator>::default();
@@ -379,4 +501,197 @@ mod prettifier {
             assert_eq!(decoded_seed, seed.into());
         })
     }
+
+    #[test]
+    fn test_lexicographic_order_matches_snowflake_order() {
+        let configurations = vec![
+            assert_ok!(IdPrettifier::lexicographic(AlphabetCodec::default(), 5, "-")),
+            assert_ok!(IdPrettifier::lexicographic(
+                AlphabetCodec::new(Alphabet::new("ABC")),
+                2,
+                "_"
+            )),
+            assert_ok!(IdPrettifier::lexicographic(
+                AlphabetCodec::new(Alphabet::new("01")),
+                1,
+                "."
+            )),
+            assert_ok!(IdPrettifier::lexicographic(
+                AlphabetCodec::new(Alphabet::new("0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ")),
+                8,
+                "~"
+            )),
+            assert_ok!(IdPrettifier::lexicographic(AlphabetCodec::default(), 18, "-")),
+        ];
+
+        let between = rand::distributions::Uniform::from(0..=i64::MAX);
+        let mut rng = rand::thread_rng();
+        let mut seeds: Vec<i64> = (0..2_000).map(|_| between.sample(&mut rng)).collect();
+        seeds.extend([0, 1, 9, 10, 99, 100, i64::MAX - 1, i64::MAX]);
+        seeds.sort_unstable();
+
+        for prettifier in configurations {
+            assert_ok!(prettifier.check_lexicographic());
+            let ids: Vec<String> = seeds.iter().map(|seed| prettifier.prettify(*seed)).collect();
+            let mut sorted = ids.clone();
+            sorted.sort();
+            assert_eq!(sorted, ids, "prettifier: {prettifier:?}");
+        }
+    }
+
+    #[test]
+    fn test_lexicographic_check_at_construction() {
+        use crate::ConfigurationError;
+
+        let descending = AlphabetCodec::new(Alphabet::new("CBA"));
+        assert_eq!(
+            assert_err!(IdPrettifier::lexicographic(descending, 5, "-")),
+            ConfigurationError::CodecNotOrderPreserving
+        );
+        assert_eq!(
+            assert_err!(IdPrettifier::lexicographic(AlphabetCodec::default(), 0, "-")),
+            ConfigurationError::InvalidPartsSize(0)
+        );
+        assert_eq!(
+            assert_err!(IdPrettifier::lexicographic(AlphabetCodec::default(), 19, "-")),
+            ConfigurationError::InvalidPartsSize(19)
+        );
+        assert_eq!(
+            assert_err!(IdPrettifier::lexicographic(AlphabetCodec::default(), 5, "")),
+            ConfigurationError::EmptyDelimiter
+        );
+
+        let default = IdPrettifier::<AlphabetCodec>::default();
+        assert_ok!(default.check_lexicographic());
+        let without_leading_zeros = IdPrettifier { leading_zeros: false, ..default.clone() };
+        assert_eq!(
+            assert_err!(without_leading_zeros.check_lexicographic()),
+            ConfigurationError::LeadingZerosRequired
+        );
+        let resized = IdPrettifier { parts_size: 8, ..default };
+        assert_eq!(
+            assert_err!(resized.check_lexicographic()),
+            ConfigurationError::InvalidMaxEncoderLength { expected: 6, actual: 4 }
+        );
+    }
+}
+
+mod obfuscation {
+    use claim::*;
+    use pretty_assertions::assert_eq;
+    use rand::Rng;
+
+    use crate::pretty::Codec;
+    use crate::{AlphabetCodec, ConfigurationError, ConversionError, IdPrettifier, ObfuscationKey, Obfuscator};
+
+    const SECRET: u128 = 0x0123_4567_89AB_CDEF_FEDC_BA98_7654_3210;
+
+    fn obfuscated(obfuscator: Obfuscator) -> IdPrettifier<AlphabetCodec> {
+        IdPrettifier {
+            obfuscator: Some(obfuscator),
+            ..IdPrettifier::default()
+        }
+    }
+
+    #[test]
+    fn test_obfuscation_round_trip() {
+        let obfuscator = Obfuscator::new(ObfuscationKey::new(1, SECRET));
+        let mut rng = rand::thread_rng();
+        let seeds = [0, 1, -1, i64::MAX, i64::MIN, 824227036833910784]
+            .into_iter()
+            .chain((0..1_000).map(|_| rng.gen::<i64>()));
+
+        for seed in seeds {
+            let (key_id, obfuscated) = obfuscator.obfuscate(seed);
+            assert_eq!(key_id, 1);
+            assert_eq!(obfuscated.is_negative(), seed.is_negative());
+            assert_eq!(assert_some!(obfuscator.reveal(key_id, obfuscated)), seed);
+        }
+    }
+
+    #[test]
+    fn test_obfuscated_prettifier_round_trip() {
+        let prettifier = obfuscated(Obfuscator::new(ObfuscationKey::new(1, SECRET)));
+        let plain = IdPrettifier::<AlphabetCodec>::default();
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..1_000 {
+            let seed = rng.gen_range(0..i64::MAX);
+            let pretty = prettifier.prettify(seed);
+            assert!(prettifier.is_valid(&pretty), "invalid obfuscated id: {pretty}");
+            assert_ne!(pretty, plain.prettify(seed));
+            assert_eq!(i64::from(assert_ok!(prettifier.to_id_seed(&pretty))), seed);
+        }
+    }
+
+    #[test]
+    fn test_obfuscation_hides_order() {
+        let obfuscator = Obfuscator::new(ObfuscationKey::new(1, SECRET));
+        let base = 824227036833910784_i64;
+        let obfuscated: Vec<_> = (0..16).map(|i| obfuscator.obfuscate(base + i).1).collect();
+        assert!(obfuscated.windows(2).any(|pair| pair[1] < pair[0]));
+        assert!(obfuscated.windows(2).all(|pair| (pair[1] - pair[0]).abs() > 16));
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        let seed = 824227036833910784_i64;
+        let before = obfuscated(Obfuscator::new(ObfuscationKey::new(1, SECRET)));
+        let old_pretty = before.prettify(seed);
+
+        let rotated = Obfuscator::new(ObfuscationKey::new(1, SECRET)).rotate(ObfuscationKey::new(2, !SECRET));
+        assert_eq!(rotated.current_key_id(), 2);
+        let after = obfuscated(rotated);
+        let new_pretty = after.prettify(seed);
+        assert_ne!(new_pretty, old_pretty);
+        assert_eq!(i64::from(assert_ok!(after.to_id_seed(&old_pretty))), seed);
+        assert_eq!(i64::from(assert_ok!(after.to_id_seed(&new_pretty))), seed);
+
+        let retired = obfuscated(
+            Obfuscator::new(ObfuscationKey::new(2, !SECRET)).with_retired_key(ObfuscationKey::new(1, SECRET)),
+        );
+        assert_eq!(i64::from(assert_ok!(retired.to_id_seed(&old_pretty))), seed);
+
+        let forgotten = obfuscated(Obfuscator::new(ObfuscationKey::new(2, !SECRET)));
+        assert!(matches!(
+            assert_err!(forgotten.to_id_seed(&old_pretty)),
+            ConversionError::UnknownObfuscationKey(1)
+        ));
+    }
+
+    #[test]
+    fn test_checksum_covers_key_id() {
+        let seed = 824227036833910784_i64;
+        let prettifier = obfuscated(
+            Obfuscator::new(ObfuscationKey::new(2, !SECRET)).with_retired_key(ObfuscationKey::new(1, SECRET)),
+        );
+        let pretty = obfuscated(Obfuscator::new(ObfuscationKey::new(1, SECRET))).prettify(seed);
+        let (key_part, rep) = assert_some!(pretty.split_once('-'));
+        assert_eq!(key_part, prettifier.encoder.encode(1));
+
+        let mistyped = format!("{}-{rep}", prettifier.encoder.encode(2));
+        assert!(!prettifier.is_valid(&mistyped));
+        assert!(matches!(
+            assert_err!(prettifier.to_id_seed(&mistyped)),
+            ConversionError::InvalidId(_)
+        ));
+    }
+
+    #[test]
+    fn test_obfuscated_invalid_ids() {
+        let prettifier = obfuscated(Obfuscator::new(ObfuscationKey::new(1, SECRET)));
+        assert!(!prettifier.is_valid("ARPJ27036GVQS07849"));
+        assert_err!(prettifier.to_id_seed("ARPJ27036GVQS07849"));
+        assert_eq!(
+            assert_err!(prettifier.check_lexicographic()),
+            ConfigurationError::Obfuscated
+        );
+    }
+
+    #[test]
+    fn test_key_debug_is_redacted() {
+        let key = ObfuscationKey::new(7, SECRET);
+        assert_eq!(key.id(), 7);
+        assert_eq!(format!("{key:?}"), "ObfuscationKey { id: 7, .. }");
+    }
 }
@@ -0,0 +1,319 @@
+//! Runtime registry of labeled types, used to recover typed ids from their string forms; e.g.,
+//! `Order::ARPJ-27036-GVQS-07849` found in a log line.
+//!
+//! Types are registered either explicitly, via [`register`], or, with the `inventory` feature, at
+//! link time via `#[derive(Label)]` with the `#[label(register)]` attribute.
+//!
+//! ```rust
+//! use pretty_snowflake::{registry, Id, Label};
+//!
+//! #[derive(Label)]
+//! struct Order;
+//!
+//! registry::register::<Order>().unwrap();
+//!
+//! let any = registry::parse_any("Order::ARPJ-27036-GVQS-07849").unwrap();
+//! assert_eq!(any.label(), "Order");
+//! let order: Id<Order> = any.downcast().unwrap();
+//! assert_eq!(order.num(), 824227036833910784);
+//! ```
+
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use thiserror::Error;
+
+use crate::pretty::Codec;
+use crate::{generator, AnyId, ConversionError, IdPrettifier, Label, PREFIX_DELIMITER};
+
+#[derive(Debug, Error)]
+pub enum RegistryError {
+    #[error("Label {label:?} is already registered for {existing}")]
+    DuplicateLabel {
+        label: &'static str,
+        existing: &'static str,
+    },
+
+    #[error("Prefix {prefix:?} is already registered for {existing}")]
+    DuplicatePrefix {
+        prefix: &'static str,
+        existing: &'static str,
+    },
+}
+
+/// Describes a labeled type to the registry.
+#[derive(Clone, Copy)]
+pub struct LabelRegistration {
+    label: fn() -> &'static str,
+    prefix: fn() -> Option<&'static str>,
+    type_name: fn() -> &'static str,
+    type_id: fn() -> TypeId,
+}
+
+impl LabelRegistration {
+    pub const fn new<T: Label + 'static>() -> Self {
+        Self {
+            label: <T as Label>::label,
+            prefix: <T as Label>::prefix,
+            type_name: std::any::type_name::<T>,
+            type_id: TypeId::of::<T>,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        (self.label)()
+    }
+
+    pub fn prefix(&self) -> Option<&'static str> {
+        (self.prefix)()
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        (self.type_name)()
+    }
+
+    pub fn type_id(&self) -> TypeId {
+        (self.type_id)()
+    }
+}
+
+impl fmt::Debug for LabelRegistration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LabelRegistration")
+            .field("label", &self.label())
+            .field("prefix", &self.prefix())
+            .field("type_name", &self.type_name())
+            .finish()
+    }
+}
+
+#[cfg(feature = "inventory")]
+inventory::collect!(LabelRegistration);
+
+#[derive(Default)]
+struct Registry {
+    by_label: HashMap<&'static str, LabelRegistration>,
+    by_prefix: HashMap<&'static str, LabelRegistration>,
+}
+
+impl Registry {
+    fn insert(&mut self, registration: LabelRegistration) -> Result<(), RegistryError> {
+        let label = registration.label();
+        if let Some(existing) = self.by_label.get(label) {
+            if existing.type_id() != registration.type_id() {
+                return Err(RegistryError::DuplicateLabel { label, existing: existing.type_name() });
+            }
+        }
+
+        let prefix = registration.prefix();
+        if let Some(existing) = prefix.and_then(|p| self.by_prefix.get(p)) {
+            if existing.type_id() != registration.type_id() {
+                return Err(RegistryError::DuplicatePrefix {
+                    prefix: prefix.unwrap_or_default(),
+                    existing: existing.type_name(),
+                });
+            }
+        }
+
+        self.by_label.insert(label, registration);
+        if let Some(prefix) = prefix {
+            self.by_prefix.insert(prefix, registration);
+        }
+        Ok(())
+    }
+
+    /// The registrations `rep` may belong to, each with the pretty form to parse for it: by prefix
+    /// first, then by label.
+    fn resolve<'r>(&self, rep: &'r str) -> Vec<(LabelRegistration, &'r str)> {
+        let prefixed = rep
+            .split_once(PREFIX_DELIMITER)
+            .and_then(|(prefix, pretty)| self.by_prefix.get(prefix).map(|r| (*r, pretty)));
+
+        let (label, pretty) = rep.rsplit_once("::").unwrap_or(("", rep));
+        let labeled = self.by_label.get(label).map(|r| (*r, pretty));
+
+        prefixed.into_iter().chain(labeled).collect()
+    }
+}
+
+static REGISTRY: Lazy<RwLock<Registry>> = Lazy::new(|| {
+    #[allow(unused_mut)]
+    let mut registry = Registry::default();
+
+    #[cfg(feature = "inventory")]
+    for registration in inventory::iter::<LabelRegistration> {
+        if let Err(err) = registry.insert(*registration) {
+            panic!("conflicting #[label(register)] types: {err}");
+        }
+    }
+
+    RwLock::new(registry)
+});
+
+/// Registers `T` so that its ids may be parsed via [`parse_any`]. Registering the same type again
+/// has no effect, but registering a different type under a label or prefix already taken is an
+/// error.
+pub fn register<T: Label + 'static>() -> Result<(), RegistryError> {
+    REGISTRY.write().unwrap().insert(LabelRegistration::new::<T>())
+}
+
+/// Looks up the type registered for a label.
+pub fn lookup(label: &str) -> Option<LabelRegistration> {
+    REGISTRY.read().unwrap().by_label.get(label).copied()
+}
+
+/// Looks up the type registered for a prefix.
+pub fn lookup_prefix(prefix: &str) -> Option<LabelRegistration> {
+    REGISTRY.read().unwrap().by_prefix.get(prefix).copied()
+}
+
+/// Parses the labeled or prefixed form of an id of any registered type, using the prettifier
+/// configured in the [`generator`] module.
+pub fn parse_any(rep: &str) -> Result<AnyId, ConversionError> {
+    parse_any_with(rep, &generator::prettifier())
+}
+
+/// Parses the labeled or prefixed form of an id of any registered type.
+///
+/// Both `Order::ARPJ-27036-GVQS-07849` and `ord_ARPJ-27036-GVQS-07849` forms are accepted. An
+/// unlabeled pretty id resolves to the type registered with an empty label, if any.
+pub fn parse_any_with<C: Codec>(rep: &str, prettifier: &IdPrettifier<C>) -> Result<AnyId, ConversionError> {
+    let candidates = REGISTRY.read().unwrap().resolve(rep);
+
+    // A label containing the prefix delimiter may begin with a registered prefix, so the label is
+    // tried when the rest of the id does not parse after the prefix.
+    let mut error = None;
+    for (registration, pretty) in candidates {
+        match prettifier.to_id_seed(pretty) {
+            Ok(snowflake) => return Ok(AnyId::from_registration(registration, snowflake, prettifier)),
+            Err(err) => {
+                error.get_or_insert(err);
+            },
+        }
+    }
+
+    Err(error.unwrap_or_else(|| ConversionError::UnknownLabel(rep.to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use claim::*;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+    use crate::{AlphabetCodec, CustomLabeling, Id};
+
+    struct Invoice;
+
+    impl Label for Invoice {
+        type Labeler = CustomLabeling;
+
+        fn labeler() -> Self::Labeler {
+            CustomLabeling::new("Invoice")
+        }
+
+        fn prefix() -> Option<&'static str> {
+            Some("inv")
+        }
+    }
+
+    struct Impostor;
+
+    impl Label for Impostor {
+        type Labeler = CustomLabeling;
+
+        fn labeler() -> Self::Labeler {
+            CustomLabeling::new("Invoice")
+        }
+    }
+
+    struct Unregistered;
+
+    impl Label for Unregistered {
+        type Labeler = CustomLabeling;
+
+        fn labeler() -> Self::Labeler {
+            CustomLabeling::new("Unregistered")
+        }
+    }
+
+    #[test]
+    fn test_parse_any() {
+        assert_ok!(register::<Invoice>());
+        assert_ok!(register::<Invoice>());
+        let prettifier = IdPrettifier::<AlphabetCodec>::default();
+
+        for rep in ["Invoice::ARPJ-27036-GVQS-07849", "inv_ARPJ-27036-GVQS-07849"] {
+            let actual = assert_ok!(parse_any_with(rep, &prettifier));
+            assert_eq!(actual.label(), "Invoice");
+            assert_eq!(actual.prefix(), Some("inv"));
+            assert_eq!(actual.num(), 824227036833910784);
+            assert!(actual.is::<Invoice>());
+            assert!(!actual.is::<Impostor>());
+
+            let invoice: Id<Invoice> = assert_ok!(actual.clone().downcast());
+            assert_eq!(invoice.num(), 824227036833910784);
+            assert_eq!(format!("{invoice}"), "inv_ARPJ-27036-GVQS-07849");
+            assert_err!(actual.downcast::<Impostor>());
+        }
+
+        assert_some!(lookup("Invoice"));
+        assert_some!(lookup_prefix("inv"));
+        assert_err!(parse_any_with("inv_ARPJ-27036-GVQS-07840", &prettifier));
+    }
+
+    struct LineItem;
+
+    impl Label for LineItem {
+        type Labeler = CustomLabeling;
+
+        fn labeler() -> Self::Labeler {
+            CustomLabeling::new("line_item")
+        }
+    }
+
+    struct Line;
+
+    impl Label for Line {
+        type Labeler = CustomLabeling;
+
+        fn labeler() -> Self::Labeler {
+            CustomLabeling::new("Line")
+        }
+
+        fn prefix() -> Option<&'static str> {
+            Some("line")
+        }
+    }
+
+    #[test]
+    fn test_label_sharing_prefix() {
+        assert_ok!(register::<LineItem>());
+        assert_ok!(register::<Line>());
+        let prettifier = IdPrettifier::<AlphabetCodec>::default();
+
+        let actual = assert_ok!(parse_any_with("line_item::ARPJ-27036-GVQS-07849", &prettifier));
+        assert!(actual.is::<LineItem>());
+        let actual = assert_ok!(parse_any_with("line_ARPJ-27036-GVQS-07849", &prettifier));
+        assert!(actual.is::<Line>());
+        assert_err!(parse_any_with("line_ARPJ-27036-GVQS-07840", &prettifier));
+    }
+
+    #[test]
+    fn test_unknown_label() {
+        let prettifier = IdPrettifier::<AlphabetCodec>::default();
+        let actual = parse_any_with("Unregistered::ARPJ-27036-GVQS-07849", &prettifier);
+        assert!(matches!(actual, Err(ConversionError::UnknownLabel(_))));
+        assert_none!(lookup(Unregistered::label()));
+    }
+
+    #[test]
+    fn test_duplicate_label() {
+        assert_ok!(register::<Invoice>());
+        let err = assert_err!(register::<Impostor>());
+        assert!(matches!(err, RegistryError::DuplicateLabel { label: "Invoice", .. }));
+    }
+}
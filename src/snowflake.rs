@@ -5,8 +5,10 @@ use std::cmp::Ordering;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds, RangeInclusive};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::MachineNode;
 
@@ -15,6 +17,81 @@ use crate::MachineNode;
 #[repr(transparent)]
 pub struct Id(i64);
 
+/// The epoch generated ids count milliseconds from.
+pub const EPOCH: SystemTime = UNIX_EPOCH;
+
+// Generated ids hold the milliseconds since the epoch above 5 bits of machine id, 5 bits of node id
+// and a 12-bit sequence.
+const TIMESTAMP_SHIFT: u32 = 22;
+const MAX_TIMESTAMP_MILLIS: i64 = i64::MAX >> TIMESTAMP_SHIFT;
+
+impl Id {
+    /// The smallest id that may be generated within the millisecond of `time`.
+    pub fn min_at(time: impl Into<SystemTime>) -> Self {
+        Self(millis_since_epoch(time.into()) << TIMESTAMP_SHIFT)
+    }
+
+    /// The largest id that may be generated within the millisecond of `time`.
+    pub fn max_at(time: impl Into<SystemTime>) -> Self {
+        Self::max_at_millis(millis_since_epoch(time.into()))
+    }
+
+    /// The bounds of the ids that may be generated within a time range, suitable for a
+    /// `WHERE id BETWEEN min AND max` query; e.g., over `yesterday..today`.
+    ///
+    /// Ids only resolve time to the millisecond, so the bounds of a range that does not start or
+    /// end on a millisecond may include ids generated slightly outside it. The bounds of an empty
+    /// time range are an empty range.
+    pub fn range_for<Ts: Copy + Into<SystemTime>>(range: impl RangeBounds<Ts>) -> RangeInclusive<Self> {
+        let start = match range.start_bound() {
+            Bound::Unbounded => Self(0),
+            Bound::Included(start) | Bound::Excluded(start) => Self::min_at(*start),
+        };
+
+        let end = match range.end_bound() {
+            Bound::Unbounded => Self(i64::MAX),
+            Bound::Included(end) => Self::max_at(*end),
+            Bound::Excluded(end) => {
+                let end: SystemTime = (*end).into();
+                if end <= EPOCH {
+                    return Self(1)..=Self(0);
+                }
+                Self::max_at(end - Duration::from_nanos(1))
+            },
+        };
+
+        start..=end
+    }
+
+    /// The time, to the millisecond, at which the id was generated.
+    pub fn timestamp(&self) -> SystemTime {
+        EPOCH + Duration::from_millis(self.millis().max(0).unsigned_abs())
+    }
+
+    #[cfg(any(feature = "metrics", feature = "tracing"))]
+    pub(crate) const MAX_SEQUENCE: i64 = 0xFFF;
+
+    pub(crate) const fn millis(self) -> i64 {
+        self.0 >> TIMESTAMP_SHIFT
+    }
+
+    #[cfg(any(feature = "metrics", feature = "tracing"))]
+    pub(crate) const fn sequence(self) -> i64 {
+        self.0 & Self::MAX_SEQUENCE
+    }
+
+    const fn max_at_millis(millis: i64) -> Self {
+        Self((millis << TIMESTAMP_SHIFT) | ((1 << TIMESTAMP_SHIFT) - 1))
+    }
+}
+
+// Times before the epoch, or beyond what an id can hold, are clamped.
+fn millis_since_epoch(time: SystemTime) -> i64 {
+    time.duration_since(EPOCH).map_or(0, |since| {
+        i64::try_from(since.as_millis()).map_or(MAX_TIMESTAMP_MILLIS, |millis| millis.min(MAX_TIMESTAMP_MILLIS))
+    })
+}
+
 impl fmt::Debug for Id {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if f.alternate() {
@@ -37,6 +114,12 @@ impl From<Id> for i64 {
     }
 }
 
+impl AsRef<i64> for Id {
+    fn as_ref(&self) -> &i64 {
+        &self.0
+    }
+}
+
 impl From<i64> for Id {
     fn from(id: i64) -> Self {
         Self(id)
@@ -52,6 +135,9 @@ impl FromStr for Id {
 }
 
 pub trait IdGenerator {
+    /// Names the generation strategy in recorded metrics and traces.
+    const STRATEGY: &'static str = "custom";
+
     fn next_id(worker: &mut Worker) -> Id;
 }
 
@@ -65,18 +151,24 @@ pub struct Generator;
 pub struct LazyGenerator;
 
 impl IdGenerator for RealTimeGenerator {
+    const STRATEGY: &'static str = "real_time";
+
     fn next_id(worker: &mut Worker) -> Id {
         worker.real_time_generate().into()
     }
 }
 
 impl IdGenerator for Generator {
+    const STRATEGY: &'static str = "basic";
+
     fn next_id(worker: &mut Worker) -> Id {
         worker.generate().into()
     }
 }
 
 impl IdGenerator for LazyGenerator {
+    const STRATEGY: &'static str = "lazy";
+
     fn next_id(worker: &mut Worker) -> Id {
         worker.lazy_generate().into()
     }
@@ -86,17 +178,36 @@ impl IdGenerator for LazyGenerator {
 #[derive(Debug, Clone)]
 pub struct SnowflakeIdGenerator<G> {
     machine_node: MachineNode,
-    worker: Arc<Mutex<Worker>>,
+    worker: Arc<Mutex<WorkerState>>,
     marker: PhantomData<G>,
 }
 
+#[derive(Debug)]
+struct WorkerState {
+    worker: Worker,
+    // The previously generated id, against which the next id is checked for sequence rollover and
+    // clock regression.
+    #[cfg(any(feature = "metrics", feature = "tracing"))]
+    last_id: Id,
+}
+
+impl WorkerState {
+    const fn new(worker: Worker) -> Self {
+        Self {
+            worker,
+            #[cfg(any(feature = "metrics", feature = "tracing"))]
+            last_id: Id(0),
+        }
+    }
+}
+
 impl<G> Default for SnowflakeIdGenerator<G> {
     fn default() -> Self {
         let machine_node = MachineNode::default();
         let worker = Worker::new(machine_node.machine_id, machine_node.node_id);
         Self {
             machine_node,
-            worker: Arc::new(Mutex::new(worker)),
+            worker: Arc::new(Mutex::new(WorkerState::new(worker))),
             marker: PhantomData,
         }
     }
@@ -111,17 +222,35 @@ impl<G: IdGenerator> SnowflakeIdGenerator<G> {
         let worker = Worker::new(machine_node.machine_id, machine_node.node_id);
         Self {
             machine_node,
-            worker: Arc::new(Mutex::new(worker)),
+            worker: Arc::new(Mutex::new(WorkerState::new(worker))),
             marker: PhantomData,
         }
     }
 
     pub fn next_id(&self) -> Id {
-        let mut w = self
+        self.next_labeled_id("")
+    }
+
+    /// Generates the next id, recording it for the given label when the `metrics` or `tracing`
+    /// features are enabled.
+    #[cfg_attr(not(any(feature = "metrics", feature = "tracing")), allow(unused_variables))]
+    pub(crate) fn next_labeled_id(&self, label: &'static str) -> Id {
+        let mut state = self
             .worker
             .lock()
             .expect("pretty snowflake generator lock already held by *this* thread.");
-        G::next_id(&mut w)
+
+        #[cfg(any(feature = "metrics", feature = "tracing"))]
+        {
+            let start = std::time::Instant::now();
+            let id = G::next_id(&mut state.worker);
+            crate::instrument::record_next_id(G::STRATEGY, label, state.last_id, id, start.elapsed());
+            state.last_id = id;
+            id
+        }
+
+        #[cfg(not(any(feature = "metrics", feature = "tracing")))]
+        G::next_id(&mut state.worker)
     }
 }
 
@@ -0,0 +1,71 @@
+//! `sqlx` mappings of `Id<T>` and `SnowflakeId` onto 64-bit integer (`BIGINT`) columns. The pretty
+//! form of a decoded `Id<T>` is reconstructed using `generator::prettifier()`.
+
+use crate::snowflake::Id as SnowflakeId;
+use crate::{generator, Id, Label};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::{Database, Decode, Encode, Type};
+
+impl<DB: Database> Type<DB> for SnowflakeId
+where
+    i64: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <i64 as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <i64 as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, DB: Database> Encode<'q, DB> for SnowflakeId
+where
+    i64: Encode<'q, DB>,
+{
+    fn encode_by_ref(&self, buf: &mut <DB as Database>::ArgumentBuffer<'q>) -> Result<IsNull, BoxDynError> {
+        <i64 as Encode<'q, DB>>::encode_by_ref(self.as_ref(), buf)
+    }
+}
+
+impl<'r, DB: Database> Decode<'r, DB> for SnowflakeId
+where
+    i64: Decode<'r, DB>,
+{
+    fn decode(value: <DB as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        <i64 as Decode<'r, DB>>::decode(value).map(Self::from)
+    }
+}
+
+impl<T: ?Sized, DB: Database> Type<DB> for Id<T>
+where
+    i64: Type<DB>,
+{
+    fn type_info() -> DB::TypeInfo {
+        <i64 as Type<DB>>::type_info()
+    }
+
+    fn compatible(ty: &DB::TypeInfo) -> bool {
+        <i64 as Type<DB>>::compatible(ty)
+    }
+}
+
+impl<'q, T: ?Sized, DB: Database> Encode<'q, DB> for Id<T>
+where
+    i64: Encode<'q, DB>,
+{
+    fn encode_by_ref(&self, buf: &mut <DB as Database>::ArgumentBuffer<'q>) -> Result<IsNull, BoxDynError> {
+        <i64 as Encode<'q, DB>>::encode_by_ref(self.snowflake().as_ref(), buf)
+    }
+}
+
+impl<'r, T: Label + ?Sized, DB: Database> Decode<'r, DB> for Id<T>
+where
+    i64: Decode<'r, DB>,
+{
+    fn decode(value: <DB as Database>::ValueRef<'r>) -> Result<Self, BoxDynError> {
+        let snowflake = <i64 as Decode<'r, DB>>::decode(value)?;
+        Ok(Self::from_snowflake(snowflake, &generator::prettifier()))
+    }
+}
@@ -1,6 +1,12 @@
 mod label {
     use pretty_assertions::assert_eq;
 
+    use std::borrow::Cow;
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant, SystemTime};
+
     use crate::{Label, Labeling};
 
     #[test]
@@ -21,6 +27,98 @@ mod label {
         let actual = <dyn Labeling>::summon::<&str>().label();
         assert_eq!(actual.as_ref(), "&str");
     }
+
+    #[test]
+    fn test_static_label() {
+        assert_eq!(<()>::label(), "");
+        assert_eq!(i64::label(), "i64");
+        assert_eq!(String::label(), "String");
+        assert_eq!(<&str>::label(), "&str");
+        assert_eq!(Option::<i64>::label(), "i64");
+        assert_eq!(
+            HashMap::<String, i64>::label(),
+            HashMap::<String, i64>::labeler().label()
+        );
+    }
+
+    #[test]
+    fn test_label_grammar() {
+        assert_eq!(i16::label(), "i16");
+        assert_eq!(str::label(), "str");
+        assert_eq!(Duration::label(), "Duration");
+        assert_eq!(Instant::label(), "Instant");
+        assert_eq!(SystemTime::label(), "SystemTime");
+
+        assert_eq!(Box::<i64>::label(), "i64");
+        assert_eq!(Arc::<str>::label(), "str");
+        assert_eq!(Rc::<String>::label(), "String");
+        assert_eq!(Cow::<'static, str>::label(), "str");
+        assert_eq!(Option::<Box<i64>>::label(), "i64");
+
+        assert_eq!(Vec::<i64>::label(), "Vec<i64>");
+        assert_eq!(VecDeque::<u8>::label(), "VecDeque<u8>");
+        assert_eq!(HashSet::<String>::label(), "HashSet<String>");
+        assert_eq!(BTreeSet::<char>::label(), "BTreeSet<char>");
+        assert_eq!(HashMap::<String, i64>::label(), "HashMap<String,i64>");
+        assert_eq!(BTreeMap::<String, Vec<u8>>::label(), "BTreeMap<String,Vec<u8>>");
+        assert_eq!(Vec::<()>::label(), "Vec<()>");
+        assert_eq!(HashMap::<(), ()>::label(), "HashMap<(),()>");
+        assert_eq!(<(i64, ())>::label(), "(i64,())");
+        assert_eq!(<[(); 2]>::label(), "[();2]");
+
+        assert_eq!(<(i64,)>::label(), "(i64,)");
+        assert_eq!(<(i64, String)>::label(), "(i64,String)");
+        assert_eq!(<(i64, (u8, char), Vec<bool>)>::label(), "(i64,(u8,char),Vec<bool>)");
+        assert_eq!(<[u8; 16]>::label(), "[u8;16]");
+        assert_eq!(<[Vec<u8>]>::label(), "[Vec<u8>]");
+        assert_eq!(HashMap::<(u8, u16), [i64; 2]>::label(), "HashMap<(u8,u16),[i64;2]>");
+    }
+
+    #[test]
+    fn test_label_grammar_matches_labeler() {
+        fn assert_consistent<T: Label + ?Sized>() {
+            assert_eq!(T::label(), T::labeler().label());
+        }
+
+        assert_consistent::<Vec<i64>>();
+        assert_consistent::<HashMap<String, Vec<u8>>>();
+        assert_consistent::<(i64, String)>();
+        assert_consistent::<[u8; 4]>();
+        assert_consistent::<Box<str>>();
+        assert_consistent::<Duration>();
+    }
+
+    #[test]
+    fn test_static_label_is_interned() {
+        let first = HashMap::<u8, u16>::label();
+        let second = HashMap::<u8, u16>::label();
+        assert!(std::ptr::eq(first, second));
+    }
+
+    #[test]
+    fn test_static_label_is_interned_by_type() {
+        macro_rules! local_label {
+            ($label:literal) => {{
+                struct Local;
+                impl Label for Local {
+                    type Labeler = crate::CustomLabeling;
+
+                    fn labeler() -> Self::Labeler {
+                        crate::CustomLabeling::new($label)
+                    }
+                }
+
+                (std::any::type_name::<Local>(), Local::label())
+            }};
+        }
+
+        // distinct types sharing a type name
+        let (first_name, first) = local_label!("First");
+        let (second_name, second) = local_label!("Second");
+        assert_eq!(first_name, second_name);
+        assert_eq!(first, "First");
+        assert_eq!(second, "Second");
+    }
 }
 
 mod labeling {
@@ -75,9 +173,28 @@ mod labeling {
 }
 
 mod snowflake {
+    use pretty_assertions::assert_eq;
     use serde_test::{assert_tokens, Token};
+    use std::time::{Duration, SystemTime};
+
+    use crate::{
+        generator, AlphabetCodec, Id, IdPrettifier, Label, MakeLabeling, RealTimeGenerator, SnowflakeId,
+        SnowflakeIdGenerator, SNOWFLAKE_EPOCH,
+    };
 
-    use crate::{RealTimeGenerator, SnowflakeIdGenerator};
+    struct Order;
+
+    impl Label for Order {
+        type Labeler = MakeLabeling<Self>;
+
+        fn labeler() -> Self::Labeler {
+            MakeLabeling::default()
+        }
+    }
+
+    fn at_millis(millis: u64) -> SystemTime {
+        SNOWFLAKE_EPOCH + Duration::from_millis(millis)
+    }
 
     #[test]
     fn test_snowflake_id_serde() {
@@ -86,4 +203,80 @@ mod snowflake {
         let id_value: i64 = id.into();
         assert_tokens(&id, &[Token::I64(id_value)]);
     }
+
+    #[test]
+    fn test_snowflake_id_time_bounds() {
+        let gen = SnowflakeIdGenerator::<RealTimeGenerator>::default();
+        let before = SystemTime::now();
+        let id = gen.next_id();
+        let after = SystemTime::now();
+
+        let bounds = SnowflakeId::range_for(before..=after);
+        assert!(bounds.contains(&id), "{id} not within {bounds:?}");
+        assert!(SnowflakeId::min_at(id.timestamp()) <= id && id <= SnowflakeId::max_at(id.timestamp()));
+        assert!(!SnowflakeId::range_for(..id.timestamp()).contains(&id));
+        assert!(!SnowflakeId::range_for(id.timestamp() + Duration::from_millis(1)..).contains(&id));
+    }
+
+    #[test]
+    fn test_snowflake_range_bounds() {
+        let day = 86_400_000;
+        let yesterday = SnowflakeId::range_for(at_millis(day)..at_millis(2 * day));
+        assert_eq!(*yesterday.start(), SnowflakeId::from((day as i64) << 22));
+        assert_eq!(*yesterday.end(), SnowflakeId::from(((2 * day as i64) << 22) - 1));
+        assert_eq!(yesterday.start().timestamp(), at_millis(day));
+        assert_eq!(yesterday.end().timestamp(), at_millis(2 * day - 1));
+
+        let inclusive = SnowflakeId::range_for(at_millis(day)..=at_millis(2 * day));
+        assert_eq!(*inclusive.end(), SnowflakeId::from(((2 * day as i64 + 1) << 22) - 1));
+
+        let partial = SnowflakeId::range_for(at_millis(day) + Duration::from_micros(500)..at_millis(day + 1));
+        assert_eq!(
+            partial,
+            SnowflakeId::min_at(at_millis(day))..=SnowflakeId::max_at(at_millis(day))
+        );
+
+        assert!(SnowflakeId::range_for(at_millis(day)..at_millis(day)).is_empty());
+        assert!(SnowflakeId::range_for(..SNOWFLAKE_EPOCH).is_empty());
+        assert_eq!(
+            SnowflakeId::range_for::<SystemTime>(..),
+            SnowflakeId::from(0)..=SnowflakeId::from(i64::MAX)
+        );
+        assert_eq!(
+            SnowflakeId::min_at(SNOWFLAKE_EPOCH - Duration::from_secs(1)),
+            SnowflakeId::from(0)
+        );
+    }
+
+    #[test]
+    fn test_pretty_id_range() {
+        let prettifier = IdPrettifier::<AlphabetCodec>::default();
+        let range = at_millis(196_510_000)..at_millis(196_520_000);
+        let bounds = Id::<Order>::range_for(range.clone(), &prettifier);
+        let expected = SnowflakeId::range_for(range.clone());
+        assert_eq!(bounds.start().num(), i64::from(*expected.start()));
+        assert_eq!(bounds.end().num(), i64::from(*expected.end()));
+        assert_eq!(bounds.start().pretty(), prettifier.prettify(*expected.start()));
+        assert_eq!(bounds.start().label(), "Order");
+
+        let configured = generator::id_range::<Order, _>(range);
+        assert_eq!(configured, bounds);
+    }
+
+    #[cfg(feature = "envelope")]
+    #[test]
+    fn test_timestamp_range() {
+        use iso8601_timestamp::Timestamp;
+
+        let start = Timestamp::parse("2022-03-01T00:00:00Z").unwrap();
+        let end = Timestamp::parse("2022-03-02T00:00:00Z").unwrap();
+        assert_eq!(
+            SnowflakeId::range_for(start..end),
+            SnowflakeId::range_for(SystemTime::from(start)..SystemTime::from(end))
+        );
+        assert_eq!(
+            SnowflakeId::range_for(start..end).start().timestamp(),
+            SystemTime::from(start)
+        );
+    }
 }
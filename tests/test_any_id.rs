@@ -0,0 +1,172 @@
+use claim::*;
+use pretty_assertions::assert_eq;
+use pretty_snowflake::{registry, AlphabetCodec, AnyId, Id, IdPrettifier, Label};
+use serde_test::{assert_ser_tokens, assert_tokens, Token};
+use std::collections::HashSet;
+
+#[derive(Label)]
+struct Order;
+
+#[derive(Label)]
+#[label(prefix = "usr")]
+struct User;
+
+#[derive(Label)]
+struct Unregistered;
+
+#[derive(Label)]
+#[label(name = "shared")]
+struct SharedA;
+
+#[derive(Label)]
+#[label(name = "shared")]
+struct SharedB;
+
+fn id<T: Label>(snowflake: i64) -> Id<T> {
+    Id::from_snowflake(snowflake, &IdPrettifier::<AlphabetCodec>::default())
+}
+
+#[test]
+fn test_heterogeneous_collection() {
+    let mut touched: Vec<AnyId> = vec![id::<User>(3).into(), id::<Order>(1).into(), id::<Order>(2).into()];
+    touched.sort();
+    let snowflakes: Vec<_> = touched.iter().map(AnyId::num).collect();
+    assert_eq!(snowflakes, vec![1, 2, 3]);
+    assert_eq!(format!("{}", touched[2]), "usr_AAAA-00000-AAAA-00037");
+
+    let set: HashSet<AnyId> = touched.iter().cloned().chain([id::<Order>(1).into()]).collect();
+    assert_eq!(set.len(), 3);
+
+    let same_snowflake: AnyId = id::<User>(1).into();
+    assert_ne!(same_snowflake, touched[0]);
+
+    let shared: HashSet<AnyId> = [id::<SharedA>(1).into(), id::<SharedB>(1).into()].into_iter().collect();
+    assert_eq!(shared.len(), 2);
+    let a: AnyId = id::<SharedA>(1).into();
+    let b: AnyId = id::<SharedB>(1).into();
+    assert_eq!(a.label(), b.label());
+    assert_ne!(a, b);
+}
+
+#[test]
+fn test_checked_downcast() {
+    let any: AnyId = id::<Order>(1).into();
+    assert_eq!(any.label(), "Order");
+    assert_eq!(any.type_name(), Some(std::any::type_name::<Order>()));
+    assert!(any.is::<Order>());
+    assert!(!any.is::<User>());
+
+    let any = assert_err!(any.downcast::<User>());
+    let order: Id<Order> = assert_ok!(any.downcast());
+    assert_eq!(order, id::<Order>(1));
+
+    let user: Id<User> = assert_ok!(AnyId::from(id::<User>(2)).downcast());
+    assert_eq!(String::from(user), "usr_AAAA-00000-AAAA-00021");
+}
+
+#[test]
+fn test_serde() {
+    assert_ok!(registry::register::<Order>());
+    let any: AnyId = id::<Order>(1).into();
+    assert_tokens(
+        &any,
+        &[
+            Token::Struct { name: "AnyId", len: 3 },
+            Token::Str("label"),
+            Token::Str("Order"),
+            Token::Str("snowflake"),
+            Token::I64(1),
+            Token::Str("pretty"),
+            Token::Str("AAAA-00000-AAAA-00013"),
+            Token::StructEnd,
+        ],
+    );
+
+    let unregistered: AnyId = id::<Unregistered>(1).into();
+    assert_ser_tokens(
+        &unregistered,
+        &[
+            Token::Struct { name: "AnyId", len: 3 },
+            Token::Str("label"),
+            Token::Str("Unregistered"),
+            Token::Str("snowflake"),
+            Token::I64(1),
+            Token::Str("pretty"),
+            Token::Str("AAAA-00000-AAAA-00013"),
+            Token::StructEnd,
+        ],
+    );
+
+    // The type of an unregistered id is not recovered on deserialization, so the id is not equal to
+    // the typed id it was serialized from.
+    let any: AnyId = assert_ok!(serde_json::from_str(&assert_ok!(serde_json::to_string(&unregistered))));
+    assert_eq!(any.label(), "Unregistered");
+    assert_eq!(any.pretty(), unregistered.pretty());
+    assert_none!(any.type_name());
+    assert_ne!(any, unregistered);
+}
+
+#[test]
+fn test_serde_prefixed() {
+    assert_ok!(registry::register::<User>());
+    let any: AnyId = id::<User>(1).into();
+    assert_tokens(
+        &any,
+        &[
+            Token::Struct { name: "AnyId", len: 3 },
+            Token::Str("label"),
+            Token::Str("User"),
+            Token::Str("snowflake"),
+            Token::I64(1),
+            Token::Str("pretty"),
+            Token::Str("usr_AAAA-00000-AAAA-00013"),
+            Token::StructEnd,
+        ],
+    );
+
+    let json = assert_ok!(serde_json::to_string(&any));
+    let any: AnyId = assert_ok!(serde_json::from_str(&json));
+    let user: Id<User> = assert_ok!(any.downcast());
+    assert_eq!(user, id::<User>(1));
+    assert_eq!(String::from(user), "usr_AAAA-00000-AAAA-00013");
+}
+
+#[test]
+fn test_deserialized_downcast() {
+    assert_ok!(registry::register::<Order>());
+    let json = r#"{"label":"Order","snowflake":1,"pretty":"AAAA-00000-AAAA-00013"}"#;
+    let any: AnyId = assert_ok!(serde_json::from_str(json));
+    assert_eq!(any.type_name(), Some(std::any::type_name::<Order>()));
+    let _: Id<Order> = assert_ok!(any.downcast());
+
+    let json = r#"{"label":"Unregistered","snowflake":1,"pretty":"AAAA-00000-AAAA-00013"}"#;
+    let any: AnyId = assert_ok!(serde_json::from_str(json));
+    assert_none!(any.type_name());
+    assert!(!any.is::<Order>());
+    assert!(!any.is::<Unregistered>());
+    assert_err!(any.downcast::<Unregistered>());
+}
+
+#[test]
+fn test_downcast_after_registration() {
+    #[derive(Label)]
+    struct Late;
+
+    let json = r#"{"label":"Late","snowflake":1,"pretty":"AAAA-00000-AAAA-00013"}"#;
+    let any: AnyId = assert_ok!(serde_json::from_str(json));
+    assert!(!any.is::<Late>());
+
+    assert_ok!(registry::register::<Late>());
+    let late: Id<Late> = assert_ok!(any.downcast());
+    assert_eq!(late, id::<Late>(1));
+
+    #[derive(Label)]
+    #[label(prefix = "lat")]
+    struct LatePrefixed;
+
+    let json = r#"{"label":"LatePrefixed","snowflake":1,"pretty":"lat_AAAA-00000-AAAA-00013"}"#;
+    let any: AnyId = assert_ok!(serde_json::from_str(json));
+    assert_ok!(registry::register::<LatePrefixed>());
+    let late: Id<LatePrefixed> = assert_ok!(any.downcast());
+    assert_eq!(String::from(late), "lat_AAAA-00000-AAAA-00013");
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "diesel")]
+
+use claim::*;
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use pretty_assertions::assert_eq;
+use pretty_snowflake::{generator, Id, Label, SnowflakeId};
+
+#[derive(Label)]
+struct Order;
+
+diesel::table! {
+    orders (id) {
+        id -> BigInt,
+        parent_id -> Nullable<BigInt>,
+    }
+}
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = orders)]
+struct OrderRow {
+    id: Id<Order>,
+    parent_id: Option<Id<Order>>,
+}
+
+fn make_connection() -> SqliteConnection {
+    let mut conn = assert_ok!(SqliteConnection::establish(":memory:"));
+    assert_ok!(
+        diesel::sql_query("CREATE TABLE orders (id BIGINT PRIMARY KEY NOT NULL, parent_id BIGINT)").execute(&mut conn)
+    );
+    conn
+}
+
+#[test]
+fn test_pretty_id_round_trip() {
+    let mut conn = make_connection();
+    let parent: Id<Order> = generator::next_id();
+    let id: Id<Order> = generator::next_id();
+
+    let row = OrderRow { id: id.clone(), parent_id: Some(parent.clone()) };
+    assert_ok!(diesel::insert_into(orders::table).values(&row).execute(&mut conn));
+
+    let actual: OrderRow = assert_ok!(orders::table.filter(orders::id.eq(&id)).first(&mut conn));
+    assert_eq!(actual.id, id);
+    assert_eq!(actual.id.pretty(), id.pretty());
+    assert_eq!(actual.id.label(), "Order");
+    assert_eq!(assert_some!(actual.parent_id).pretty(), parent.pretty());
+
+    let raw: i64 = assert_ok!(orders::table.select(orders::id).first(&mut conn));
+    assert_eq!(raw, id.num());
+}
+
+#[test]
+fn test_snowflake_id_round_trip() {
+    let mut conn = make_connection();
+    let id: SnowflakeId = 824227036833910784.into();
+
+    assert_ok!(diesel::insert_into(orders::table)
+        .values(orders::id.eq(id))
+        .execute(&mut conn));
+
+    let actual: SnowflakeId = assert_ok!(orders::table.select(orders::id).first(&mut conn));
+    assert_eq!(actual, id);
+
+    let pretty: Id<Order> = assert_ok!(orders::table.select(orders::id).filter(orders::id.eq(id)).first(&mut conn));
+    assert_eq!(pretty.pretty(), "ARPJ-27036-GVQS-07849");
+}
@@ -0,0 +1,90 @@
+use pretty_assertions::assert_eq;
+use pretty_snowflake::{Label, Labeling};
+use std::marker::PhantomData;
+
+#[derive(Label)]
+struct Order;
+
+#[derive(Clone, Label)]
+#[label(name = "ord")]
+struct NamedOrder;
+
+#[derive(Label)]
+#[label(none)]
+struct Anonymous;
+
+#[derive(Label)]
+#[label(rename_all = "snake_case")]
+struct OrderItem;
+
+#[derive(Label)]
+#[label(rename_all = "kebab-case")]
+enum OrderStatus {
+    #[allow(dead_code)]
+    Open,
+}
+
+#[derive(Label)]
+struct Wrapper<T>(PhantomData<T>);
+
+#[derive(Label)]
+#[label(name = "page")]
+struct Page<T>(PhantomData<T>);
+
+#[derive(Label)]
+#[label(rename_all = "snake_case")]
+struct KeyedPage<'a, K, V: Clone>(PhantomData<&'a (K, V)>);
+
+#[derive(Label)]
+#[label(none)]
+struct Opaque<T>(PhantomData<T>);
+
+fn label_of<T: Label>() -> String {
+    let label = T::labeler().label().into_owned();
+    assert_eq!(T::label(), label);
+    label
+}
+
+#[test]
+fn test_default_label() {
+    assert_eq!(label_of::<Order>(), "Order");
+    assert_eq!(label_of::<Wrapper<Order>>(), "Wrapper<Order>");
+}
+
+#[test]
+fn test_named_label() {
+    assert_eq!(label_of::<NamedOrder>(), "ord");
+    assert_eq!(label_of::<Page<Order>>(), "page<Order>");
+    assert_eq!(label_of::<Page<NamedOrder>>(), "page<ord>");
+}
+
+#[test]
+fn test_named_label_with_unlabeled_param() {
+    assert_eq!(label_of::<Page<()>>(), "page<()>");
+    assert_eq!(label_of::<Page<Anonymous>>(), "page<()>");
+    assert_eq!(label_of::<Vec<Anonymous>>(), "Vec<()>");
+}
+
+#[test]
+fn test_no_label() {
+    assert_eq!(label_of::<Anonymous>(), "");
+    assert_eq!(label_of::<Opaque<Order>>(), "");
+}
+
+#[test]
+fn test_rename_all_label() {
+    assert_eq!(label_of::<OrderItem>(), "order_item");
+    assert_eq!(label_of::<OrderStatus>(), "order-status");
+    assert_eq!(
+        label_of::<KeyedPage<'static, Order, NamedOrder>>(),
+        "keyed_page<Order,ord>"
+    );
+}
+
+#[test]
+fn test_generic_label_is_interned() {
+    let first = Page::<Order>::label();
+    let second = Page::<Order>::label();
+    assert_eq!(first, "page<Order>");
+    assert!(std::ptr::eq(first, second));
+}
@@ -0,0 +1,142 @@
+use claim::*;
+use pretty_assertions::assert_eq;
+use pretty_snowflake::{
+    generator, AlphabetCodec, ConversionError, Id, IdPrettifier, Label, LabeledRealtimeIdGenerator, PrettyId,
+};
+use serde::{Deserialize, Serialize};
+use serde_test::{assert_de_tokens_error, assert_tokens, Token};
+
+const SNOWFLAKE: i64 = 824227036833910784;
+const PRETTY: &str = "ARPJ-27036-GVQS-07849";
+
+#[derive(Label)]
+#[label(prefix = "ord")]
+struct Order;
+
+#[derive(Label)]
+#[label(prefix = "usr")]
+struct User;
+
+#[derive(Debug, Clone, PartialEq, PrettyId)]
+struct OrderId(Id<Order>);
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Row {
+    #[serde(with = "pretty_snowflake::id_serde::pretty")]
+    order: Id<Order>,
+    #[serde(with = "pretty_snowflake::id_serde::labeled")]
+    user: Id<User>,
+}
+
+fn order() -> Id<Order> {
+    Id::from_snowflake(SNOWFLAKE, &IdPrettifier::<AlphabetCodec>::default())
+}
+
+#[test]
+fn test_prefixed_string_forms() {
+    let id = order();
+    assert_eq!(id.prefix(), Some("ord"));
+    assert_eq!(id.pretty(), PRETTY);
+    assert_eq!(format!("{id}"), "ord_ARPJ-27036-GVQS-07849");
+    assert_eq!(String::from(id), "ord_ARPJ-27036-GVQS-07849");
+
+    let generated = LabeledRealtimeIdGenerator::<Order>::default().next_id();
+    assert!(String::from(generated).starts_with("ord_"));
+    assert!(String::from(generator::next_id::<User>()).starts_with("usr_"));
+}
+
+#[test]
+fn test_relabel_switches_prefix() {
+    let user: Id<User> = order().unchecked_relabel();
+    assert_eq!(String::from(user), "usr_ARPJ-27036-GVQS-07849");
+
+    let unprefixed: Id<()> = order().unchecked_relabel();
+    assert_none!(unprefixed.prefix());
+    assert_eq!(String::from(unprefixed), PRETTY);
+}
+
+#[test]
+fn test_parse_requires_prefix() {
+    let prettifier = IdPrettifier::<AlphabetCodec>::default();
+    let actual = assert_ok!(Id::<Order>::parse_with("ord_ARPJ-27036-GVQS-07849", &prettifier));
+    assert_eq!(actual, order());
+    assert_eq!(actual.prefix(), Some("ord"));
+
+    let err = assert_err!(Id::<User>::parse_with("ord_ARPJ-27036-GVQS-07849", &prettifier));
+    assert!(matches!(err, ConversionError::InvalidPrefix { expected: "usr", .. }));
+    assert_err!(Id::<Order>::parse_with(PRETTY, &prettifier));
+    assert_err!(Id::<Order>::parse_with("ordARPJ-27036-GVQS-07849", &prettifier));
+}
+
+#[test]
+fn test_serde_includes_prefix() {
+    let id = order();
+    assert_tokens(
+        &id,
+        &[
+            Token::Struct { name: "Id", len: 2 },
+            Token::Str("snowflake"),
+            Token::I64(SNOWFLAKE),
+            Token::Str("pretty"),
+            Token::Str("ord_ARPJ-27036-GVQS-07849"),
+            Token::StructEnd,
+        ],
+    );
+
+    assert_de_tokens_error::<Id<User>>(
+        &[
+            Token::Struct { name: "Id", len: 2 },
+            Token::Str("snowflake"),
+            Token::I64(SNOWFLAKE),
+            Token::Str("pretty"),
+            Token::Str("ord_ARPJ-27036-GVQS-07849"),
+            Token::StructEnd,
+        ],
+        "Expected id prefix \"usr\" in \"ord_ARPJ-27036-GVQS-07849\"",
+    );
+}
+
+#[test]
+fn test_id_serde_includes_prefix() {
+    let row = Row { order: order(), user: order().unchecked_relabel() };
+    assert_tokens(
+        &row,
+        &[
+            Token::Struct { name: "Row", len: 2 },
+            Token::Str("order"),
+            Token::Str("ord_ARPJ-27036-GVQS-07849"),
+            Token::Str("user"),
+            Token::Str("usr_ARPJ-27036-GVQS-07849"),
+            Token::StructEnd,
+        ],
+    );
+
+    assert_de_tokens_error::<Row>(
+        &[
+            Token::Struct { name: "Row", len: 2 },
+            Token::Str("order"),
+            Token::Str("usr_ARPJ-27036-GVQS-07849"),
+        ],
+        "Expected id prefix \"ord\" in \"usr_ARPJ-27036-GVQS-07849\"",
+    );
+}
+
+#[test]
+fn test_pretty_id_from_snowflake_includes_prefix() {
+    let actual = OrderId::from(SNOWFLAKE);
+    assert_eq!(actual.id(), &order());
+    assert_eq!(actual.id().prefix(), Some("ord"));
+    assert_eq!(actual.to_string(), "ord_ARPJ-27036-GVQS-07849");
+    assert_eq!(assert_ok!(actual.to_string().parse::<OrderId>()), actual);
+    assert_tokens(
+        &actual,
+        &[
+            Token::Struct { name: "Id", len: 2 },
+            Token::Str("snowflake"),
+            Token::I64(SNOWFLAKE),
+            Token::Str("pretty"),
+            Token::Str("ord_ARPJ-27036-GVQS-07849"),
+            Token::StructEnd,
+        ],
+    );
+}
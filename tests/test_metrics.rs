@@ -0,0 +1,80 @@
+#![cfg(feature = "metrics")]
+
+use metrics::Key;
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+use pretty_assertions::assert_eq;
+use pretty_snowflake::instrument::{IDS_GENERATED, PRETTIFY_SECONDS, SEQUENCE_EXHAUSTED, WAIT_SECONDS};
+use pretty_snowflake::{generator, Label, LabeledLazyIdGenerator};
+
+#[derive(Label)]
+struct Order;
+
+// Taking a snapshot drains recorded histograms, so each test takes a single snapshot.
+fn snapshot(snapshotter: &Snapshotter) -> Vec<(Key, DebugValue)> {
+    snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| (key.key().clone(), value))
+        .collect()
+}
+
+fn find<'s>(snapshot: &'s [(Key, DebugValue)], name: &str, labels: &[(&str, &str)]) -> Option<&'s DebugValue> {
+    snapshot.iter().find_map(|(key, value)| {
+        let matches = key.name() == name
+            && labels
+                .iter()
+                .all(|(k, v)| key.labels().any(|label| label.key() == *k && label.value() == *v));
+        matches.then_some(value)
+    })
+}
+
+#[test]
+fn test_generation_metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let gen = LabeledLazyIdGenerator::<Order>::default();
+
+    metrics::with_local_recorder(&recorder, || {
+        for _ in 0..5_000 {
+            gen.next_id();
+        }
+    });
+
+    let snapshot = snapshot(&snapshotter);
+    assert_eq!(
+        find(&snapshot, IDS_GENERATED, &[("strategy", "lazy"), ("label", "Order")]),
+        Some(&DebugValue::Counter(5_000))
+    );
+    assert_eq!(
+        find(&snapshot, SEQUENCE_EXHAUSTED, &[("strategy", "lazy")]),
+        Some(&DebugValue::Counter(1))
+    );
+    assert!(matches!(
+        find(&snapshot, WAIT_SECONDS, &[("strategy", "lazy")]),
+        Some(DebugValue::Histogram(waits)) if waits.len() == 1
+    ));
+    assert!(matches!(
+        find(&snapshot, PRETTIFY_SECONDS, &[]),
+        Some(DebugValue::Histogram(latencies)) if latencies.len() == 5_000
+    ));
+}
+
+#[test]
+fn test_shared_generator_records_label() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+
+    metrics::with_local_recorder(&recorder, || {
+        generator::next_id::<Order>();
+    });
+
+    assert_eq!(
+        find(
+            &snapshot(&snapshotter),
+            IDS_GENERATED,
+            &[("strategy", "real_time"), ("label", "Order")]
+        ),
+        Some(&DebugValue::Counter(1))
+    );
+}
@@ -0,0 +1,87 @@
+use claim::*;
+use once_cell::sync::Lazy;
+use pretty_assertions::assert_eq;
+use pretty_snowflake::{
+    Alphabet, AlphabetCodec, Id, IdPrettifier, Label, LabeledRealtimeIdGenerator, PrettyId, PrettyIdGenerator,
+};
+use serde_test::{assert_tokens, Token};
+use std::str::FromStr;
+
+#[derive(Label)]
+struct Order;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, PrettyId)]
+struct OrderId(Id<Order>);
+
+static CUSTOM_PRETTIFIER: Lazy<IdPrettifier<AlphabetCodec>> = Lazy::new(|| IdPrettifier {
+    encoder: AlphabetCodec::new(Alphabet::new("ABC")),
+    parts_size: 4,
+    delimiter: '_'.to_string(),
+    leading_zeros: false,
+    ..IdPrettifier::default()
+});
+
+static CUSTOM_GENERATOR: Lazy<LabeledRealtimeIdGenerator<Order>> =
+    Lazy::new(|| PrettyIdGenerator::single_node(CUSTOM_PRETTIFIER.clone()));
+
+#[derive(Debug, Clone, PartialEq, PrettyId)]
+#[pretty_id(generator = "CUSTOM_GENERATOR", prettifier = "CUSTOM_PRETTIFIER")]
+struct CustomOrderId(Id<Order>);
+
+#[test]
+fn test_pretty_id_next() {
+    let a = OrderId::next();
+    let b = OrderId::next();
+    assert!(a < b);
+    assert_eq!(a.id().label(), "Order");
+    assert_eq!(format!("{a}"), format!("Order::{}", a.id().pretty()));
+    assert_eq!(format!("{a:#}"), a.id().num().to_string());
+}
+
+#[test]
+fn test_pretty_id_from_str() {
+    let expected = OrderId::next();
+    let pretty: String = expected.clone().into_inner().into();
+    assert_eq!(assert_ok!(OrderId::from_str(&pretty)), expected);
+    assert_eq!(assert_ok!(OrderId::from_str(&expected.to_string())), expected);
+    assert_err!(OrderId::from_str(&format!("User::{pretty}")));
+    assert_err!(OrderId::from_str("not an id"));
+}
+
+#[test]
+fn test_pretty_id_conversions() {
+    let actual = OrderId::from(824227036833910784);
+    assert_eq!(actual.id().pretty(), "ARPJ-27036-GVQS-07849");
+    assert_eq!(actual.id().label(), "Order");
+
+    let id: Id<Order> = actual.clone().into();
+    assert_eq!(OrderId::from(id.clone()), actual);
+    assert_eq!(actual.as_ref(), &id);
+}
+
+#[test]
+fn test_pretty_id_serde() {
+    let actual = OrderId::from(824227036833910784);
+    assert_tokens(
+        &actual,
+        &[
+            Token::Struct { name: "Id", len: 2 },
+            Token::Str("snowflake"),
+            Token::I64(824227036833910784),
+            Token::Str("pretty"),
+            Token::Str("ARPJ-27036-GVQS-07849"),
+            Token::StructEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_pretty_id_bound_generator_and_prettifier() {
+    let actual = CustomOrderId::next();
+    assert_eq!(actual.id().pretty(), CUSTOM_PRETTIFIER.prettify(actual.id().num()));
+
+    let from_num = CustomOrderId::from(1234567);
+    assert_eq!(from_num.id().pretty(), "BCAACAB_5671");
+    assert_eq!(assert_ok!(CustomOrderId::from_str("BCAACAB_5671")), from_num);
+    assert_eq!(assert_ok!(CustomOrderId::from_str("Order::BCAACAB_5671")), from_num);
+}
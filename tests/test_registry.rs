@@ -0,0 +1,41 @@
+#![cfg(feature = "inventory")]
+
+use claim::*;
+use pretty_assertions::assert_eq;
+use pretty_snowflake::{generator, registry, AnyId, Id, Label};
+
+#[derive(Label)]
+#[label(register)]
+struct Order;
+
+#[derive(Label)]
+#[label(prefix = "usr", register)]
+struct User;
+
+#[test]
+fn test_derive_registration() {
+    let order = assert_some!(registry::lookup("Order"));
+    assert_eq!(order.type_name(), std::any::type_name::<Order>());
+
+    let user = assert_some!(registry::lookup_prefix("usr"));
+    assert_eq!(user.label(), "User");
+}
+
+#[test]
+fn test_parse_any_from_log_line() {
+    let order: Id<Order> = generator::next_id();
+    let user: Id<User> = generator::next_id();
+    let log_line = format!("touched {order} and {user}");
+
+    let parsed: Vec<AnyId> = log_line
+        .split_whitespace()
+        .filter_map(|word| registry::parse_any(word).ok())
+        .collect();
+    assert_eq!(parsed.len(), 2);
+
+    let actual_order: Id<Order> = assert_ok!(parsed[0].clone().downcast());
+    assert_eq!(actual_order, order);
+    let actual_user: Id<User> = assert_ok!(parsed[1].clone().downcast());
+    assert_eq!(actual_user, user);
+    assert_err!(parsed[1].clone().downcast::<Order>());
+}
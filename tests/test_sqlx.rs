@@ -0,0 +1,67 @@
+#![cfg(feature = "sqlx")]
+
+use claim::*;
+use pretty_assertions::assert_eq;
+use pretty_snowflake::{generator, Id, Label, SnowflakeId};
+use sqlx::sqlite::SqlitePool;
+
+#[derive(Label)]
+struct Order;
+
+async fn make_pool() -> SqlitePool {
+    let pool = assert_ok!(SqlitePool::connect("sqlite::memory:").await);
+    assert_ok!(
+        sqlx::query("CREATE TABLE orders (id BIGINT PRIMARY KEY NOT NULL, parent_id BIGINT)")
+            .execute(&pool)
+            .await
+    );
+    pool
+}
+
+#[tokio::test]
+async fn test_pretty_id_round_trip() {
+    let pool = make_pool().await;
+    let id: Id<Order> = generator::next_id();
+
+    assert_ok!(
+        sqlx::query("INSERT INTO orders (id, parent_id) VALUES (?, ?)")
+            .bind(&id)
+            .bind(None::<Id<Order>>)
+            .execute(&pool)
+            .await
+    );
+
+    let (actual, parent): (Id<Order>, Option<Id<Order>>) =
+        assert_ok!(sqlx::query_as("SELECT id, parent_id FROM orders").fetch_one(&pool).await);
+    assert_eq!(actual, id);
+    assert_eq!(actual.pretty(), id.pretty());
+    assert_eq!(actual.label(), "Order");
+    assert_none!(parent);
+
+    let raw: i64 = assert_ok!(sqlx::query_scalar("SELECT id FROM orders").fetch_one(&pool).await);
+    assert_eq!(raw, id.num());
+}
+
+#[tokio::test]
+async fn test_snowflake_id_round_trip() {
+    let pool = make_pool().await;
+    let id: SnowflakeId = 824227036833910784.into();
+
+    assert_ok!(
+        sqlx::query("INSERT INTO orders (id) VALUES (?)")
+            .bind(id)
+            .execute(&pool)
+            .await
+    );
+
+    let actual: SnowflakeId = assert_ok!(sqlx::query_scalar("SELECT id FROM orders").fetch_one(&pool).await);
+    assert_eq!(actual, id);
+
+    let pretty: Id<Order> = assert_ok!(
+        sqlx::query_scalar("SELECT id FROM orders WHERE id = ?")
+            .bind(id)
+            .fetch_one(&pool)
+            .await
+    );
+    assert_eq!(pretty.pretty(), "ARPJ-27036-GVQS-07849");
+}